@@ -0,0 +1,247 @@
+use std::{
+    collections::HashMap,
+    fs,
+    io::{self, IsTerminal},
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use toml::{Table, Value};
+
+use crate::error::{Error, Result};
+use crate::params::Params;
+use crate::runner::Day;
+
+const DEFAULT_CONFIG: &str = "aoc.toml";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Text,
+    Json,
+}
+
+impl FromStr for Format {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "text" => Ok(Format::Text),
+            "json" => Ok(Format::Json),
+            _ => Err(Error::new(format!(
+                "unknown format '{s}', expected 'text' or 'json'"
+            ))),
+        }
+    }
+}
+
+/// Runner settings, read from `aoc.toml` (or `--config <path>`) and then
+/// overridden by command line flags.
+///
+/// ```toml
+/// input_dir = "input"
+/// default_day = "all"
+/// format = "text"
+/// timing = true
+/// color = false
+///
+/// [day8]
+/// connections = 1000
+/// ```
+///
+/// Unknown parameter names are rejected when the day is run.
+#[derive(Debug, Clone)]
+pub struct Config {
+    pub input_dir: PathBuf,
+    pub default_day: Option<String>,
+    pub format: Format,
+    pub timing: bool,
+    pub color: bool,
+    /// `[dayN]` tables from the config file.
+    pub days: HashMap<u32, Vec<(String, String)>>,
+    /// `--param [dayN.]name=value` overrides, applied after the config file.
+    pub overrides: Vec<(Option<u32>, String, String)>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            input_dir: PathBuf::from("input"),
+            default_day: None,
+            format: Format::Text,
+            timing: true,
+            color: io::stdout().is_terminal(),
+            days: HashMap::new(),
+            overrides: Vec::new(),
+        }
+    }
+}
+
+fn value_to_string(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+fn expect_bool(key: &str, value: &Value) -> Result<bool> {
+    value
+        .as_bool()
+        .ok_or_else(|| Error::new(format!("'{key}' must be true or false")))
+}
+
+fn expect_str<'a>(key: &str, value: &'a Value) -> Result<&'a str> {
+    value
+        .as_str()
+        .ok_or_else(|| Error::new(format!("'{key}' must be a string")))
+}
+
+/// Splits `name=value` or `dayN.name=value`.
+fn parse_override(arg: &str) -> Result<(Option<u32>, String, String)> {
+    let (name, value) = arg
+        .split_once('=')
+        .ok_or_else(|| Error::new(format!("expected name=value, got '{arg}'")))?;
+
+    let (day, name) = match name.split_once('.') {
+        Some((day, name)) => {
+            let day = day
+                .strip_prefix("day")
+                .and_then(|n| n.parse().ok())
+                .ok_or_else(|| Error::new(format!("expected dayN.name=value, got '{arg}'")))?;
+            (Some(day), name)
+        }
+        None => (None, name),
+    };
+
+    Ok((day, name.to_string(), value.to_string()))
+}
+
+fn declares(day: &Day, name: &str) -> bool {
+    day.params.iter().any(|param| param.name == name)
+}
+
+impl Config {
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = fs::read_to_string(path)
+            .map_err(|err| Error::new(format!("could not read {}: {err}", path.display())))?;
+        let table: Table = text
+            .parse()
+            .map_err(|err| Error::new(format!("{}: {err}", path.display())))?;
+
+        let mut config = Config::default();
+        for (key, value) in table.iter() {
+            match key.as_str() {
+                "input_dir" => config.input_dir = PathBuf::from(expect_str(key, value)?),
+                "default_day" => config.default_day = Some(value_to_string(value)),
+                "format" => config.format = expect_str(key, value)?.parse()?,
+                "timing" => config.timing = expect_bool(key, value)?,
+                "color" => config.color = expect_bool(key, value)?,
+                _ => {
+                    let day: u32 = key
+                        .strip_prefix("day")
+                        .and_then(|n| n.parse().ok())
+                        .ok_or_else(|| Error::new(format!("unknown config key '{key}'")))?;
+                    let section = value
+                        .as_table()
+                        .ok_or_else(|| Error::new(format!("'{key}' must be a table")))?;
+
+                    let params = config.days.entry(day).or_default();
+                    for (name, value) in section.iter() {
+                        params.push((name.clone(), value_to_string(value)));
+                    }
+                }
+            }
+        }
+
+        Ok(config)
+    }
+
+    /// Builds the config from the command line, returning it along with the
+    /// remaining positional arguments.
+    pub fn from_args(args: &[String]) -> Result<(Self, Vec<String>)> {
+        let config_path = args
+            .iter()
+            .position(|arg| arg == "--config")
+            .map(|i| {
+                args.get(i + 1)
+                    .map(PathBuf::from)
+                    .ok_or_else(|| Error::new("--config needs a path"))
+            })
+            .transpose()?;
+
+        let mut config = match config_path {
+            Some(path) => Config::load(&path)?,
+            None if Path::new(DEFAULT_CONFIG).exists() => Config::load(Path::new(DEFAULT_CONFIG))?,
+            None => Config::default(),
+        };
+
+        let mut positional = Vec::new();
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            let mut value = || {
+                args.next()
+                    .ok_or_else(|| Error::new(format!("{arg} needs a value")))
+            };
+
+            match arg.as_str() {
+                "--config" => {
+                    value()?;
+                }
+                "--input" => config.input_dir = PathBuf::from(value()?),
+                "--format" => config.format = value()?.parse()?,
+                "--param" => config.overrides.push(parse_override(value()?)?),
+                "--timing" => config.timing = true,
+                "--no-timing" => config.timing = false,
+                "--color" => config.color = true,
+                "--no-color" => config.color = false,
+                flag if flag.starts_with("--") => {
+                    return Err(Error::new(format!("unknown option '{flag}'")));
+                }
+                _ => positional.push(arg.clone()),
+            }
+        }
+
+        Ok((config, positional))
+    }
+
+    pub fn input_path(&self, day: u32) -> PathBuf {
+        self.input_dir.join(format!("day{day}.txt"))
+    }
+
+    pub fn answers_path(&self, day: u32) -> PathBuf {
+        self.input_dir.join(format!("day{day}.answers"))
+    }
+
+    /// The day's declared defaults, overridden by its config table and then
+    /// by any `--param` flags aimed at it. A flag without a day prefix only
+    /// applies to the days that declare its name.
+    pub fn params(&self, day: &Day) -> Result<Params> {
+        let mut params = Params::with_defaults(day.params);
+
+        for (name, value) in self.days.get(&day.number).into_iter().flatten() {
+            params.set(name, value)?;
+        }
+        for (target, name, value) in self.overrides.iter() {
+            let applies = match target {
+                Some(target) => *target == day.number,
+                None => declares(day, name),
+            };
+            if applies {
+                params.set(name, value)?;
+            }
+        }
+
+        Ok(params)
+    }
+
+    /// Checks that every `--param` without a day prefix is declared by at
+    /// least one of the days about to run, as `params` skips the others.
+    pub fn check_overrides<'a>(&self, days: impl IntoIterator<Item = &'a Day>) -> Result<()> {
+        let days: Vec<&Day> = days.into_iter().collect();
+        for (target, name, _) in self.overrides.iter() {
+            if target.is_none() && !days.iter().any(|day| declares(day, name)) {
+                return Err(Error::new(format!("unknown parameter '{name}'")));
+            }
+        }
+        Ok(())
+    }
+}
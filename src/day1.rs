@@ -1,49 +1,331 @@
-use std::fs;
+use crate::error::{Error, Result};
+use crate::params::{Param, Params};
+use crate::runner::{Day, Solution, View};
 
-fn read_input() -> Vec<i32> {
-    let input = fs::read_to_string("input/day1.txt").unwrap();
+/// A rotation as a signed click count, right (towards higher numbers)
+/// positive, along with the line it came from and the direction written
+/// there, which the sign can't keep for a rotation by zero.
+#[derive(Debug, Clone, Copy)]
+struct Rotation {
+    line: usize,
+    direction: char,
+    amount: i64,
+}
+
+/// Parses one rotation: a direction `L` or `R`, optional whitespace, and an
+/// amount which may carry the sign matching its direction (`R+5`, `L-5`).
+fn parse_rotation(line: &str) -> std::result::Result<(char, i64), String> {
+    let mut chars = line.chars();
+    let direction = chars.next().unwrap_or_default();
+    let signum = match direction {
+        'L' => -1,
+        'R' => 1,
+        _ => return Err(format!("unknown direction '{direction}'")),
+    };
+    let amount = chars.as_str().trim();
+
+    let (sign, digits) = match amount.split_at_checked(1) {
+        Some(("+", digits)) => (Some(1), digits.trim_start()),
+        Some(("-", digits)) => (Some(-1), digits.trim_start()),
+        _ => (None, amount),
+    };
+    if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(format!("invalid amount '{amount}'"));
+    }
+    let clicks: i64 = digits
+        .parse()
+        .map_err(|_| format!("amount '{amount}' is out of range"))?;
+
+    if sign.is_some_and(|sign| sign != signum) {
+        return Err(format!("sign of '{line}' contradicts its direction"));
+    }
+
+    Ok((direction, signum * clicks))
+}
+
+/// Rotations, one per line. Blank lines and `#` comments are skipped.
+fn read_input(input: &str) -> Result<Vec<Rotation>> {
     input
         .lines()
-        .map(|line| {
-            (if line.starts_with("L") { 1 } else { -1 }) * line[1..].parse::<i32>().unwrap()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+        .map(|(line, text)| {
+            let (direction, amount) =
+                parse_rotation(text).map_err(|message| Error::at(line, message))?;
+            Ok(Rotation {
+                line,
+                direction,
+                amount,
+            })
         })
         .collect()
 }
 
-fn part1(input: &[i32]) -> i32 {
-    let mut x = 50;
-    let mut hit_zero = 0;
+/// A dial numbered `0..size`, pointing at `position`.
+#[derive(Debug, Clone, Copy)]
+struct Dial {
+    size: i64,
+    position: i64,
+}
 
-    for &turn in input {
-        x = (x + turn).rem_euclid(100);
-        if x == 0 {
-            hit_zero += 1;
+impl Dial {
+    fn new(size: i64, start: i64) -> Self {
+        Self {
+            size,
+            position: start.rem_euclid(size),
         }
     }
 
-    hit_zero
+    /// How many clicks of a rotation by `amount` point at `target`, counting
+    /// where it stops but not where it starts.
+    fn clicks_on(&self, amount: i64, target: i64) -> i64 {
+        // widened so that rotations near i64::MAX can't overflow
+        let (x, amount, target, size) = (
+            i128::from(self.position),
+            i128::from(amount),
+            i128::from(target),
+            i128::from(self.size),
+        );
+
+        // count the k congruent to target in (x, x + amount] or [x + amount, x)
+        let (low, high) = if amount >= 0 {
+            (x, x + amount)
+        } else {
+            (x + amount - 1, x - 1)
+        };
+
+        ((high - target).div_euclid(size) - (low - target).div_euclid(size)) as i64
+    }
+
+    fn rotate(&mut self, amount: i64) {
+        let position = i128::from(self.position) + i128::from(amount);
+        self.position = position.rem_euclid(i128::from(self.size)) as i64;
+    }
+
+    /// `targets` reduced onto the dial, without duplicates.
+    fn positions(&self, targets: &[i64]) -> Vec<i64> {
+        let mut positions: Vec<i64> = targets.iter().map(|t| t.rem_euclid(self.size)).collect();
+        positions.sort();
+        positions.dedup();
+        positions
+    }
+
+    /// Rotations that finish pointing at one of `targets`.
+    fn count_landings(mut self, rotations: &[Rotation], targets: &[i64]) -> usize {
+        let targets = self.positions(targets);
+
+        rotations
+            .iter()
+            .filter(|rotation| {
+                self.rotate(rotation.amount);
+                targets.contains(&self.position)
+            })
+            .count()
+    }
+
+    /// Clicks, across all rotations, that point at one of `targets`.
+    fn count_clicks(mut self, rotations: &[Rotation], targets: &[i64]) -> i128 {
+        let targets = self.positions(targets);
+
+        rotations
+            .iter()
+            .map(|rotation| {
+                let clicks: i128 = targets
+                    .iter()
+                    .map(|&t| i128::from(self.clicks_on(rotation.amount, t)))
+                    .sum();
+                self.rotate(rotation.amount);
+                clicks
+            })
+            .sum()
+    }
+
+    /// For every position, how many clicks pointed at it and how many
+    /// rotations stopped on it, in O(rotations + size).
+    fn histogram(mut self, rotations: &[Rotation]) -> Histogram {
+        let size = self.size as usize;
+        let mut full_turns: i128 = 0;
+        // +1/-1 at the ends of each partial arc, prefix summed at the end
+        let mut arcs = vec![0i64; size + 1];
+        let mut landings = vec![0i64; size];
+
+        for rotation in rotations {
+            let amount = rotation.amount;
+            let clicks = amount.unsigned_abs();
+            full_turns += i128::from(clicks / self.size as u64);
+
+            let rest = (clicks % self.size as u64) as i64;
+            if rest > 0 {
+                let first = if amount > 0 {
+                    self.position + 1
+                } else {
+                    self.position - rest
+                };
+                let first = first.rem_euclid(self.size) as usize;
+                let last = first + rest as usize;
+
+                arcs[first] += 1;
+                if last <= size {
+                    arcs[last] -= 1;
+                } else {
+                    arcs[size] -= 1;
+                    arcs[0] += 1;
+                    arcs[last - size] -= 1;
+                }
+            }
+
+            self.rotate(amount);
+            landings[self.position as usize] += 1;
+        }
+
+        let clicks = arcs[..size]
+            .iter()
+            .scan(full_turns, |count, &delta| {
+                *count += i128::from(delta);
+                Some(*count)
+            })
+            .collect();
+
+        Histogram { clicks, landings }
+    }
 }
 
-fn part2(input: &[i32]) -> i32 {
-    let mut x = 50;
-    let mut passed_zero = 0;
+struct Histogram {
+    clicks: Vec<i128>,
+    landings: Vec<i64>,
+}
 
-    for &turn in input {
-        let next_x = x + turn;
-        passed_zero += (next_x / 100).abs() + i32::from(x != 0 && next_x <= 0);
-        x = next_x.rem_euclid(100);
+fn dial_from(params: &Params) -> Result<Dial> {
+    let size: i64 = params.get("dial_size")?;
+    if size <= 0 {
+        return Err(Error::new("dial_size must be positive"));
     }
+    Ok(Dial::new(size, params.get("start")?))
+}
+
+fn parse_targets(targets: &str) -> Result<Vec<i64>> {
+    targets
+        .split(',')
+        .map(|t| {
+            t.trim()
+                .parse()
+                .map_err(|_| Error::new(format!("invalid target position '{t}'")))
+        })
+        .collect()
+}
 
-    passed_zero
+fn part1(input: &[Rotation], dial: Dial, targets: &[i64]) -> usize {
+    dial.count_landings(input, targets)
 }
 
-pub fn run() {
-    println!("\n--- day1 ---");
-    let input = read_input();
+fn part2(input: &[Rotation], dial: Dial, targets: &[i64]) -> i128 {
+    dial.count_clicks(input, targets)
+}
+
+/// Largest dial the histogram view will print a line for every position of.
+const MAX_HISTOGRAM: i64 = 1 << 20;
+
+fn show_histogram(text: &str, params: &Params) -> Result<()> {
+    let dial = dial_from(params)?;
+    if dial.size > MAX_HISTOGRAM {
+        return Err(Error::new(format!(
+            "dial_size is too large for a histogram, at most {MAX_HISTOGRAM}"
+        )));
+    }
+    let histogram = dial.histogram(&read_input(text)?);
+
+    println!("position  clicks  landings");
+    for (position, (clicks, landings)) in histogram
+        .clicks
+        .iter()
+        .zip(histogram.landings.iter())
+        .enumerate()
+    {
+        println!("{position:>8}  {clicks:>6}  {landings:>8}");
+    }
+
+    Ok(())
+}
+
+/// Replays the rotations one at a time, showing what each contributed.
+fn show_trace(text: &str, params: &Params) -> Result<()> {
+    let mut dial = dial_from(params)?;
+    let targets = dial.positions(&parse_targets(&params.get::<String>("targets")?)?);
+    let csv: bool = params.get("csv")?;
+
+    if csv {
+        println!("line,rotation,before,after,landed,clicks,total_landed,total_clicks");
+    } else {
+        println!("line  rotation  before  after  landed  clicks  total_landed  total_clicks");
+    }
+
+    let mut total_landed = 0;
+    let mut total_clicks = 0;
+    for rotation in read_input(text)? {
+        let amount = rotation.amount;
+        let instruction = format!("{}{}", rotation.direction, amount.unsigned_abs());
+
+        let before = dial.position;
+        let clicks: i128 = targets
+            .iter()
+            .map(|&t| i128::from(dial.clicks_on(amount, t)))
+            .sum();
+        dial.rotate(amount);
+        let landed = i64::from(targets.contains(&dial.position));
+
+        total_landed += landed;
+        total_clicks += clicks;
+
+        let line = rotation.line;
+        let after = dial.position;
+        if csv {
+            println!(
+                "{line},{instruction},{before},{after},{landed},{clicks},{total_landed},{total_clicks}"
+            );
+        } else {
+            println!(
+                "{line:>4}  {instruction:>8}  {before:>6}  {after:>5}  {landed:>6}  {clicks:>6}  \
+                 {total_landed:>12}  {total_clicks:>12}"
+            );
+        }
+    }
+
+    Ok(())
+}
+
+pub const DAY: Day = Day {
+    number: 1,
+    params: &[
+        Param::new("dial_size", "100", "number of positions on the dial"),
+        Param::new("start", "50", "position the dial starts at"),
+        Param::new("targets", "0", "comma separated positions to count"),
+        Param::new("csv", "false", "print the trace view as CSV"),
+    ],
+    parse: |text| read_input(text).map(drop),
+    run,
+    visualize: None,
+    views: &[
+        View {
+            name: "histogram",
+            help: "clicks and landings on every dial position",
+            show: show_histogram,
+        },
+        View {
+            name: "trace",
+            help: "each rotation with its clicks on the targets and running totals",
+            show: show_trace,
+        },
+    ],
+};
+
+fn run(text: &str, params: &Params, solution: &mut Solution) -> Result<()> {
+    let dial = dial_from(params)?;
+    let targets = parse_targets(&params.get::<String>("targets")?)?;
+    let input = solution.time("parse", || read_input(text))?;
 
-    let result1 = part1(&input);
-    println!("part1: {result1}");
+    solution.part("part1", || part1(&input, dial, &targets));
+    solution.part("part2", || part2(&input, dial, &targets));
 
-    let result2 = part2(&input);
-    println!("part2: {result2}");
+    Ok(())
 }
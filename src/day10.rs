@@ -1,8 +1,9 @@
 use microlp::{ComparisonOp, OptimizationDirection, Problem, Variable};
-use std::{
-    collections::{HashSet, VecDeque},
-    fs, time,
-};
+use std::collections::{HashSet, VecDeque};
+
+use crate::error::{Error, Result};
+use crate::params::Params;
+use crate::runner::{Day, Solution};
 
 type BitFlags = u16;
 
@@ -16,65 +17,89 @@ struct Puzzle {
     machines: Vec<Machine>,
 }
 
-fn parse_diagram(input: &str) -> BitFlags {
+fn strip_brackets(input: &str, open: char, close: char) -> Result<&str> {
+    input
+        .strip_prefix(open)
+        .and_then(|s| s.strip_suffix(close))
+        .ok_or_else(|| Error::new(format!("expected {open}...{close}, found '{input}'")))
+}
+
+fn bit(i: usize) -> Result<BitFlags> {
+    u32::try_from(i)
+        .ok()
+        .and_then(|i| BitFlags::from(1u8).checked_shl(i))
+        .ok_or_else(|| Error::new(format!("light {i} is out of range")))
+}
+
+fn parse_diagram(input: &str) -> Result<BitFlags> {
     let mut flags: BitFlags = 0;
 
-    for (i, c) in input[1..input.len() - 1].chars().enumerate() {
+    for (i, c) in strip_brackets(input, '[', ']')?.chars().enumerate() {
         if c == '#' {
-            flags |= 1 << i;
+            flags |= bit(i)?;
         }
     }
 
-    return flags;
+    Ok(flags)
 }
 
-fn parse_buttons(input: &str) -> BitFlags {
+fn parse_buttons(input: &str) -> Result<BitFlags> {
     let mut flags: BitFlags = 0;
 
-    for c in input[1..input.len() - 1].split(',') {
-        let i: BitFlags = c.parse().unwrap();
-        flags |= 1 << i;
+    for c in strip_brackets(input, '(', ')')?.split(',') {
+        let i: usize = c
+            .parse()
+            .map_err(|_| Error::new(format!("invalid light index '{c}'")))?;
+        flags |= bit(i)?;
     }
 
-    return flags;
+    Ok(flags)
 }
 
-impl Puzzle {
-    fn load(path: &str) -> Puzzle {
-        let text = fs::read_to_string(path).unwrap();
+fn parse_machine(line: &str) -> Result<Machine> {
+    let split_line: Vec<&str> = line.split_whitespace().collect();
+    if let [first, middle @ .., last] = split_line.as_slice() {
+        let start: BitFlags = parse_diagram(first)?;
+        let buttons: Vec<BitFlags> = middle
+            .iter()
+            .map(|x| parse_buttons(x))
+            .collect::<Result<_>>()?;
+        let target: Vec<u32> = strip_brackets(last, '{', '}')?
+            .split(",")
+            .map(|x| {
+                x.parse()
+                    .map_err(|_| Error::new(format!("invalid joltage '{x}'")))
+            })
+            .collect::<Result<_>>()?;
+        bit(target.len().saturating_sub(1))?;
+
+        Ok(Machine {
+            start,
+            buttons,
+            target,
+        })
+    } else {
+        Err(Error::new("expected a diagram, buttons and joltages"))
+    }
+}
 
+impl Puzzle {
+    fn load(text: &str) -> Result<Puzzle> {
         let problems: Vec<Machine> = text
             .lines()
-            .map(|line| {
-                let split_line: Vec<&str> = line.split_whitespace().collect();
-                if let [first, middle @ .., last] = split_line.as_slice() {
-                    let start: BitFlags = parse_diagram(&first);
-                    let buttons: Vec<BitFlags> = middle.iter().map(|x| parse_buttons(x)).collect();
-                    let target: Vec<u32> = last[1..last.len() - 1]
-                        .split(",")
-                        .map(|x| x.parse().unwrap())
-                        .collect();
-
-                    Machine {
-                        start,
-                        buttons,
-                        target,
-                    }
-                } else {
-                    panic!()
-                }
-            })
-            .collect();
+            .enumerate()
+            .map(|(i, line)| parse_machine(line).map_err(|err| Error::at(i + 1, err.message)))
+            .collect::<Result<_>>()?;
 
-        Puzzle { machines: problems }
+        Ok(Puzzle { machines: problems })
     }
 }
 
-fn part1(puzzle: &Puzzle) {
+fn part1(puzzle: &Puzzle) -> u16 {
     let mut queue = VecDeque::<(u16, BitFlags)>::new();
     let mut seen = HashSet::<u16>::new();
 
-    let result: u16 = puzzle
+    puzzle
         .machines
         .iter()
         .filter_map(|p| {
@@ -97,14 +122,13 @@ fn part1(puzzle: &Puzzle) {
                 }
             }
 
-            return None;
+            None
         })
-        .sum();
-    println!("Part 1: {}", result);
+        .sum()
 }
 
-fn part2(puzzle: &Puzzle) {
-    let solutions: f64 = puzzle
+fn part2(puzzle: &Puzzle) -> f64 {
+    puzzle
         .machines
         .iter()
         .map(|machine| {
@@ -131,25 +155,23 @@ fn part2(puzzle: &Puzzle) {
             problem.solve().unwrap().objective()
         })
         .map(|x| x.round())
-        .sum();
-
-    println!("Part 2: {}", solutions);
+        .sum()
 }
 
-pub fn run() {
-    println!("--- Day 10 ---");
-
-    let puzzle = Puzzle::load("input/day10.txt");
-
-    let start1 = time::Instant::now();
-    part1(&puzzle);
-    let delta1 = start1.elapsed();
+pub const DAY: Day = Day {
+    number: 10,
+    params: &[],
+    parse: |text| Puzzle::load(text).map(drop),
+    run,
+    visualize: None,
+    views: &[],
+};
 
-    println!("{:.2?}", delta1);
+fn run(text: &str, _params: &Params, solution: &mut Solution) -> Result<()> {
+    let puzzle = solution.time("parse", || Puzzle::load(text))?;
 
-    let start2 = time::Instant::now();
-    part2(&puzzle);
-    let delta2 = start2.elapsed();
+    solution.part("part1", || part1(&puzzle));
+    solution.part("part2", || part2(&puzzle));
 
-    println!("{:.2?}", delta2);
+    Ok(())
 }
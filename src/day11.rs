@@ -1,4 +1,8 @@
-use std::{collections::HashMap, fs};
+use std::collections::HashMap;
+
+use crate::error::{Error, Result};
+use crate::params::{Param, Params};
+use crate::runner::{Day, Solution};
 
 #[derive(Debug)]
 struct Puzzle {
@@ -7,35 +11,46 @@ struct Puzzle {
 }
 
 impl Puzzle {
-    fn load(path: &str) -> Puzzle {
-        let text = fs::read_to_string(path).unwrap();
-
+    fn load(text: &str) -> Result<Puzzle> {
         let mut codes: HashMap<String, usize> = [("out".to_string(), 0)].into_iter().collect();
         let mut connections: Vec<Vec<usize>> = vec![Vec::new()];
 
+        let mut devices: Vec<(&str, &str)> = Vec::new();
         for (i, line) in text.lines().enumerate() {
-            codes.entry(line[..3].to_string()).insert_entry(i + 1);
+            let (name, outputs) = line
+                .split_once(':')
+                .filter(|(name, _)| !name.trim().is_empty())
+                .ok_or_else(|| Error::at(i + 1, "expected 'device: outputs...'"))?;
+            devices.push((name.trim(), outputs));
+
+            codes.entry(name.trim().to_string()).insert_entry(i + 1);
             connections.push(Vec::new());
         }
 
-        for line in text.lines() {
-            let conn: Vec<usize> = line
+        for (name, outputs) in devices {
+            let conn: Vec<usize> = outputs
                 .split_whitespace()
-                .flat_map(|x| codes.get(&x[..3]))
+                .flat_map(|x| codes.get(x))
                 .copied()
                 .collect();
 
-            if let [first, rest @ ..] = conn.as_slice() {
-                connections[*first].extend(rest);
-            }
+            connections[codes[name]].extend(conn);
         }
 
-        Puzzle { codes, connections }
+        Ok(Puzzle { codes, connections })
+    }
+
+    fn code(&self, name: &str) -> Result<usize> {
+        self.codes
+            .get(name)
+            .copied()
+            .ok_or_else(|| Error::new(format!("no device named '{name}'")))
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn count_paths(
-    connections: &Vec<Vec<usize>>,
+    connections: &[Vec<usize>],
     from: usize,
     to: usize,
     fft_code: usize,
@@ -75,15 +90,15 @@ fn count_paths(
     }
 }
 
-fn solve(puzzle: &Puzzle, from: &str, part1: bool) -> usize {
+fn solve(puzzle: &Puzzle, from: &str, waypoints: (&str, &str), part1: bool) -> Result<usize> {
     let mut memory = HashMap::<(usize, bool, bool), usize>::new();
 
-    let from = *puzzle.codes.get(from).unwrap();
-    let to = *puzzle.codes.get(&"out".to_string()).unwrap();
-    let fft_code = *puzzle.codes.get(&"fft".to_string()).unwrap();
-    let dac_code = *puzzle.codes.get(&"dac".to_string()).unwrap();
+    let from = puzzle.code(from)?;
+    let to = puzzle.code("out")?;
+    let fft_code = puzzle.code(waypoints.0)?;
+    let dac_code = puzzle.code(waypoints.1)?;
 
-    count_paths(
+    Ok(count_paths(
         &puzzle.connections,
         from,
         to,
@@ -92,12 +107,35 @@ fn solve(puzzle: &Puzzle, from: &str, part1: bool) -> usize {
         part1,
         part1,
         &mut memory,
-    )
+    ))
 }
 
-pub fn run() {
-    let puzzle = Puzzle::load("input/day11.txt");
+pub const DAY: Day = Day {
+    number: 11,
+    params: &[
+        Param::new("part1_start", "you", "device part 1 counts paths from"),
+        Param::new("part2_start", "svr", "device part 2 counts paths from"),
+        Param::new("waypoint1", "fft", "first device part 2 paths must visit"),
+        Param::new("waypoint2", "dac", "second device part 2 paths must visit"),
+    ],
+    parse: |text| Puzzle::load(text).map(drop),
+    run,
+    visualize: None,
+    views: &[],
+};
+
+fn run(text: &str, params: &Params, solution: &mut Solution) -> Result<()> {
+    let part1_start: String = params.get("part1_start")?;
+    let part2_start: String = params.get("part2_start")?;
+    let waypoint1: String = params.get("waypoint1")?;
+    let waypoint2: String = params.get("waypoint2")?;
+    let waypoints = (waypoint1.as_str(), waypoint2.as_str());
+    let puzzle = solution.time("parse", || Puzzle::load(text))?;
+
+    let part1 = solution.time("part1", || solve(&puzzle, &part1_start, waypoints, true))?;
+    solution.answer("part1", part1);
+    let part2 = solution.time("part2", || solve(&puzzle, &part2_start, waypoints, false))?;
+    solution.answer("part2", part2);
 
-    println!("Part 1: {}", solve(&puzzle, "you", true));
-    println!("Part 2: {}", solve(&puzzle, "svr", false));
+    Ok(())
 }
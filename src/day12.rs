@@ -1,5 +1,8 @@
-use std::{fs, iter::zip};
+use std::iter::zip;
 
+use crate::error::{Error, Result};
+use crate::params::Params;
+use crate::runner::{Day, Solution};
 
 #[derive(Debug)]
 struct Shape {
@@ -7,11 +10,13 @@ struct Shape {
 }
 
 impl Shape {
-    fn from_string(input: &str) -> Shape {
+    fn from_string(input: &str) -> Result<Shape> {
         // remove the number and colon
-        let (_, input) = input.split_once("\n").unwrap();
+        let (_, input) = input
+            .split_once("\n")
+            .ok_or_else(|| Error::new(format!("invalid shape '{input}'")))?;
         let area: usize = input.chars().filter(|&c| c == '#').count();
-        Shape { area }
+        Ok(Shape { area })
     }
 }
 
@@ -23,15 +28,23 @@ struct Space {
 }
 
 impl Space {
-    fn from_string(input: &str) -> Space {
-        let (dims, counts) = input.split_once(": ").unwrap();
-        let (width, height) = dims.split_once("x").unwrap();
-
-        let counts: Vec<usize> = counts.split_whitespace().map(|c| c.parse().unwrap()).collect();
-        let width: usize = width.parse().unwrap();
-        let height: usize = height.parse().unwrap();
-
-        Space { width, height, counts }
+    fn from_string(input: &str) -> Result<Space> {
+        let invalid = || Error::new(format!("invalid region '{input}'"));
+        let (dims, counts) = input.split_once(": ").ok_or_else(invalid)?;
+        let (width, height) = dims.split_once("x").ok_or_else(invalid)?;
+
+        let counts: Vec<usize> = counts
+            .split_whitespace()
+            .map(|c| c.parse().map_err(|_| invalid()))
+            .collect::<Result<_>>()?;
+        let width: usize = width.parse().map_err(|_| invalid())?;
+        let height: usize = height.parse().map_err(|_| invalid())?;
+
+        Ok(Space {
+            width,
+            height,
+            counts,
+        })
     }
 
     fn can_fit(&self, shapes: &[Shape]) -> bool {
@@ -48,7 +61,7 @@ impl Space {
             return false;
         }
 
-        return true;
+        true
     }
 }
 
@@ -58,26 +71,46 @@ struct Puzzle {
 }
 
 impl Puzzle {
-    fn load(path: &str) -> Puzzle {
-        let input = fs::read_to_string(path).unwrap();
-
+    fn load(input: &str) -> Result<Puzzle> {
         let test: Vec<&str> = input.split("\n\n").collect();
 
-        let (&last, rest) = test.split_last().unwrap();
+        let (&last, rest) = test
+            .split_last()
+            .ok_or_else(|| Error::new("expected shapes followed by regions"))?;
 
-        let shapes: Vec<Shape> = rest.iter().map(|&s| Shape::from_string(s)).collect();
-        let spaces: Vec<Space> = last.lines().map(Space::from_string).collect();
-        
-        Puzzle { shapes, spaces }
+        let shapes: Vec<Shape> = rest
+            .iter()
+            .map(|&s| Shape::from_string(s))
+            .collect::<Result<_>>()?;
+        let spaces: Vec<Space> = last
+            .lines()
+            .map(Space::from_string)
+            .collect::<Result<_>>()?;
+
+        Ok(Puzzle { shapes, spaces })
     }
 
     fn part1(&self) -> usize {
-        self.spaces.iter().filter(|&space| space.can_fit(&self.shapes)).count()
+        self.spaces
+            .iter()
+            .filter(|&space| space.can_fit(&self.shapes))
+            .count()
     }
 }
 
-pub fn run() {
-    let puzzle = Puzzle::load("input/day12.txt");
+pub const DAY: Day = Day {
+    number: 12,
+    params: &[],
+    parse: |text| Puzzle::load(text).map(drop),
+    run,
+    visualize: None,
+    views: &[],
+};
+
+fn run(text: &str, _params: &Params, solution: &mut Solution) -> Result<()> {
+    let puzzle = solution.time("parse", || Puzzle::load(text))?;
+
+    solution.part("part1", || puzzle.part1());
 
-    println!("Part 1: {}", puzzle.part1());
+    Ok(())
 }
@@ -1,54 +1,475 @@
-use std::fs;
+use std::iter::Sum;
+use std::ops::{Add, RangeInclusive, Sub};
+use std::str::FromStr;
 
-fn read_input() -> Vec<(i64, i64)> {
-    let input = fs::read_to_string("input/day2.txt").unwrap();
+use crate::error::{Error, Result};
+use crate::interval::IntervalSet;
+use crate::params::{Param, Params};
+use crate::runner::{Day, Solution, View};
 
+/// Ranges of IDs written in `radix`, separated by commas and/or newlines.
+fn read_input(input: &str, radix: u32) -> Result<Vec<(u128, u128)>> {
     input
-        .split(",")
-        .map(|pair| {
-            let (start, end) = pair.split_once("-").unwrap();
-            (start.parse().unwrap(), end.parse().unwrap())
+        .lines()
+        .enumerate()
+        .flat_map(|(i, line)| line.split(',').map(move |pair| (i + 1, pair.trim())))
+        .filter(|(_, pair)| !pair.is_empty())
+        .map(|(line, pair)| {
+            let invalid = || Error::at(line, format!("invalid range '{pair}'"));
+            let (start, end) = pair.split_once('-').ok_or_else(invalid)?;
+            let start = u128::from_str_radix(start.trim(), radix).map_err(|_| invalid())?;
+            let end = u128::from_str_radix(end.trim(), radix).map_err(|_| invalid())?;
+            if start > end {
+                return Err(Error::at(
+                    line,
+                    format!("range '{pair}' ends before it starts"),
+                ));
+            }
+            Ok((start, end))
         })
         .collect()
 }
 
-fn is_invalid_part1(i: i64) -> bool {
-    let str_version = i.to_string();
-    let length = str_version.len();
-    if length % 2 == 0 {
-        let mid = length / 2;
-        let (left, right) = str_version.split_at(mid);
-        left == right
-    } else {
-        false
+/// Which repeated IDs count as invalid.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Rule {
+    /// A block repeated exactly this many times.
+    Exactly(u32),
+    /// A block repeated at least this many times.
+    AtLeast(u32),
+    /// A block of one of these lengths, repeated at least twice.
+    Blocks(Vec<u32>),
+}
+
+impl Rule {
+    /// Block lengths that make a `length` digit ID invalid.
+    fn periods(&self, length: u32) -> Vec<u32> {
+        (1..length)
+            .filter(|&d| length.is_multiple_of(d))
+            .filter(|&d| match self {
+                Rule::Exactly(k) => length / d == *k,
+                Rule::AtLeast(k) => length / d >= *k,
+                Rule::Blocks(blocks) => blocks.contains(&d),
+            })
+            .collect()
+    }
+}
+
+impl FromStr for Rule {
+    type Err = Error;
+
+    /// `exactly:K`, `at-least:K` or `blocks:D,D,...`.
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = || {
+            Error::new(format!(
+                "invalid rule '{s}', expected 'exactly:K', 'at-least:K' or 'blocks:D,...'"
+            ))
+        };
+        let (kind, value) = s.split_once(':').ok_or_else(invalid)?;
+        let number = |n: &str| n.trim().parse::<u32>().map_err(|_| invalid());
+
+        match kind.trim() {
+            "exactly" => Ok(Rule::Exactly(number(value)?)),
+            "at-least" => Ok(Rule::AtLeast(number(value)?)),
+            "blocks" => Ok(Rule::Blocks(
+                value.split(',').map(number).collect::<Result<_>>()?,
+            )),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+/// How many IDs matched and what they add up to, if that fits in a u128.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Tally {
+    count: u128,
+    sum: Option<u128>,
+}
+
+impl Default for Tally {
+    fn default() -> Self {
+        Self {
+            count: 0,
+            sum: Some(0),
+        }
+    }
+}
+
+impl Add for Tally {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self {
+            count: self.count + other.count,
+            sum: self.sum.zip(other.sum).and_then(|(a, b)| a.checked_add(b)),
+        }
+    }
+}
+
+impl Sub for Tally {
+    type Output = Self;
+
+    /// Only used to take away a subset of the IDs, so the sum can't
+    /// underflow, and it stays unknown if the whole was unknown.
+    fn sub(self, other: Self) -> Self {
+        Self {
+            count: self.count - other.count,
+            sum: self.sum.zip(other.sum).map(|(a, b)| a - b),
+        }
     }
 }
 
-fn is_invalid_part2(i: i64) -> bool {
-    let chars: Vec<char> = i.to_string().chars().collect();
-    let length = chars.len();
-    (1..=(length / 2))
-        .filter(|&chunk_size| length.is_multiple_of(chunk_size))
-        .any(|chunk_size| {
-            let first = &chars[..chunk_size];
-            chars.chunks(chunk_size).all(|item| item == first)
+impl Sum for Tally {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Tally::default(), |a, b| a + b)
+    }
+}
+
+/// Number of digits `id` has in `radix`.
+fn digits(id: u128, radix: u128) -> u32 {
+    id.checked_ilog(radix).map_or(1, |n| n + 1)
+}
+
+/// `10..010..01` in `radix`, which turns a `period` digit block into the
+/// `length` digit ID repeating it.
+fn repunit(radix: u128, length: u32, period: u32) -> u128 {
+    // built up a block at a time, as radix^length itself may not fit
+    let shift = radix.pow(period);
+    (0..length / period).fold(0, |repunit, _| repunit * shift + 1)
+}
+
+/// `low..=high` split by number of digits, as `(length, low, high)`.
+fn by_length(radix: u128, low: u128, high: u128) -> impl Iterator<Item = (u32, u128, u128)> {
+    (1..=digits(high, radix))
+        .map(move |length| {
+            let low = low.max(radix.pow(length - 1));
+            let high = high.min(radix.checked_pow(length).map_or(u128::MAX, |n| n - 1));
+            (length, low, high)
         })
+        .filter(|(_, low, high)| low <= high)
+}
+
+/// The blocks of `period` digits whose repetition is a `length` digit ID in
+/// `low..=high`.
+fn blocks(radix: u128, low: u128, high: u128, length: u32, period: u32) -> RangeInclusive<u128> {
+    let repunit = repunit(radix, length, period);
+    let first = radix.pow(period - 1).max(low.div_ceil(repunit));
+    let last = (radix.pow(period) - 1).min(high / repunit);
+    first..=last
 }
 
-fn solve(input: &[(i64, i64)], predicate: impl Fn(i64) -> bool) -> i64 {
+/// The `length` digit IDs in `low..=high` made of one block of `period`
+/// digits repeated.
+fn tally_with_period(radix: u128, low: u128, high: u128, length: u32, period: u32) -> Tally {
+    let blocks = blocks(radix, low, high, length, period);
+    if blocks.is_empty() {
+        return Tally::default();
+    }
+
+    let (first, last) = blocks.into_inner();
+    let count = last - first + 1;
+    // first + last is even whenever count is odd
+    let blocks_sum = if count.is_multiple_of(2) {
+        (count / 2).checked_mul(first + last)
+    } else {
+        count.checked_mul((first + last) / 2)
+    };
+
+    Tally {
+        count,
+        sum: blocks_sum.and_then(|sum| sum.checked_mul(repunit(radix, length, period))),
+    }
+}
+
+/// The IDs in `low..=high` built from a repeated block whose length makes
+/// them invalid under `rule`, without visiting the IDs in between.
+fn tally_invalid(rule: &Rule, radix: u128, low: u128, high: u128) -> Tally {
+    let mut total = Tally::default();
+
+    for (length, low, high) in by_length(radix, low, high) {
+        // an ID repeating blocks of d digits also repeats blocks of every
+        // multiple of d, so split the tallies by the shortest block instead.
+        // Only blocks dividing an invalid one are needed, which keeps every
+        // partial sum below the total.
+        let periods = rule.periods(length);
+        let mut shortest: Vec<(u32, Tally)> = Vec::new();
+        for period in (1..length).filter(|&d| periods.iter().any(|p| p.is_multiple_of(d))) {
+            let shorter: Tally = shortest
+                .iter()
+                .filter(|(d, _)| period.is_multiple_of(*d))
+                .map(|&(_, tally)| tally)
+                .sum();
+            let tally = tally_with_period(radix, low, high, length, period) - shorter;
+            shortest.push((period, tally));
+        }
+
+        total = total + shortest.into_iter().map(|(_, tally)| tally).sum();
+    }
+
+    total
+}
+
+/// The first `limit` invalid IDs in `low..=high`, in order.
+fn first_invalid(rule: &Rule, radix: u128, low: u128, high: u128, limit: usize) -> Vec<u128> {
+    let mut found = Vec::new();
+
+    for (length, low, high) in by_length(radix, low, high) {
+        let mut ids: Vec<u128> = rule
+            .periods(length)
+            .into_iter()
+            .flat_map(|period| {
+                let repunit = repunit(radix, length, period);
+                blocks(radix, low, high, length, period)
+                    .take(limit)
+                    .map(move |block| block * repunit)
+            })
+            .collect();
+        ids.sort();
+        ids.dedup();
+
+        found.extend(ids);
+        if found.len() >= limit {
+            break;
+        }
+    }
+
+    found.truncate(limit);
+    found
+}
+
+/// The shortest block length that makes `id` invalid under `rule`, checked
+/// on its digits.
+fn invalid_block(rule: &Rule, radix: u128, id: u128) -> Option<u32> {
+    let length = digits(id, radix);
+    rule.periods(length).into_iter().find(|&period| {
+        // dividing rather than multiplying out a block, which can overflow
+        let repunit = repunit(radix, length, period);
+        id.is_multiple_of(repunit) && id / repunit < radix.pow(period)
+    })
+}
+
+fn to_radix(mut n: u128, radix: u128) -> String {
+    let mut digits = Vec::new();
+    loop {
+        digits.extend(char::from_digit((n % radix) as u32, radix as u32));
+        n /= radix;
+        if n == 0 {
+            break;
+        }
+    }
+    digits.iter().rev().collect()
+}
+
+fn solve(input: &[(u128, u128)], rule: &Rule, radix: u128) -> Result<u128> {
+    input
+        .iter()
+        .map(|&(start, finish)| tally_invalid(rule, radix, start, finish))
+        .sum::<Tally>()
+        .sum
+        .ok_or_else(|| Error::new("sum of invalid IDs overflows u128"))
+}
+
+/// The ranges joined wherever they overlap or touch, so that IDs they
+/// share are only counted once.
+fn merged(input: &[(u128, u128)]) -> Vec<(u128, u128)> {
     input
         .iter()
-        .flat_map(|&(start, finish)| (start..=finish).filter(|&i| predicate(i)))
-        .sum()
+        .copied()
+        .collect::<IntervalSet<u128>>()
+        .iter()
+        .collect()
+}
+
+fn radix_from(params: &Params) -> Result<u32> {
+    let radix: u32 = params.get("radix")?;
+    if !(2..=36).contains(&radix) {
+        return Err(Error::new("radix must be between 2 and 36"));
+    }
+    Ok(radix)
 }
 
-pub fn run() {
-    println!("\n--- day2 ---");
+/// Every range with its invalid IDs and the block that made each invalid.
+fn show_report(text: &str, params: &Params) -> Result<()> {
+    let radix = radix_from(params)?;
+    let rule: Rule = match params.get::<String>("report")?.as_str() {
+        "part1" => params.get("part1")?,
+        "part2" => params.get("part2")?,
+        other => {
+            return Err(Error::new(format!(
+                "invalid report '{other}', expected 'part1' or 'part2'"
+            )));
+        }
+    };
+    let limit = match params.get("list")? {
+        0 => usize::MAX,
+        n => n,
+    };
+    let input = read_input(text, radix)?;
+    let radix = u128::from(radix);
+    let show = |(start, finish)| format!("{}-{}", to_radix(start, radix), to_radix(finish, radix));
 
-    let input = read_input();
-    let part1 = solve(&input, &is_invalid_part1);
-    println!("part1: {part1}");
+    for (i, &(start, finish)) in input.iter().enumerate() {
+        let tally = tally_invalid(&rule, radix, start, finish);
 
-    let part2 = solve(&input, &is_invalid_part2);
-    println!("part2: {part2}");
+        let overlaps: Vec<String> = input
+            .iter()
+            .enumerate()
+            .filter(|&(j, &(other_start, other_finish))| {
+                j != i && other_start <= finish && start <= other_finish
+            })
+            .map(|(_, &range)| show(range))
+            .collect();
+        let overlaps = if overlaps.is_empty() {
+            String::new()
+        } else {
+            format!(" (overlaps {})", overlaps.join(", "))
+        };
+
+        let sum = tally
+            .sum
+            .map_or("too large for u128".to_string(), |sum| sum.to_string());
+        println!(
+            "{}{overlaps}: {} invalid, sum {sum}",
+            show((start, finish)),
+            tally.count
+        );
+        let ids = first_invalid(&rule, radix, start, finish, limit);
+        for &id in &ids {
+            let period = invalid_block(&rule, radix, id).unwrap_or_default();
+            println!(
+                "  {}  block {} x {}",
+                to_radix(id, radix),
+                to_radix(id % radix.pow(period), radix),
+                digits(id, radix) / period.max(1)
+            );
+        }
+        if tally.count > ids.len() as u128 {
+            println!("  ... {} more", tally.count - ids.len() as u128);
+        }
+    }
+
+    Ok(())
+}
+
+pub const DAY: Day = Day {
+    number: 2,
+    params: &[
+        Param::new("radix", "10", "base the IDs are written in, 2 to 36"),
+        Param::new(
+            "merge",
+            "false",
+            "merge overlapping ranges so shared IDs count once",
+        ),
+        Param::new(
+            "part1",
+            "exactly:2",
+            "invalid IDs in part 1: exactly:K, at-least:K or blocks:D,...",
+        ),
+        Param::new(
+            "part2",
+            "at-least:2",
+            "invalid IDs in part 2: exactly:K, at-least:K or blocks:D,...",
+        ),
+        Param::new(
+            "report",
+            "part2",
+            "rule the report view classifies IDs by: part1 or part2",
+        ),
+        Param::new(
+            "list",
+            "10",
+            "invalid IDs listed per range by the report view, 0 for all",
+        ),
+    ],
+    parse: |text| read_input(text, 10).map(drop),
+    run,
+    visualize: None,
+    views: &[View {
+        name: "report",
+        help: "invalid IDs in each range and the repeated block behind each",
+        show: show_report,
+    }],
+};
+
+fn run(text: &str, params: &Params, solution: &mut Solution) -> Result<()> {
+    let radix = radix_from(params)?;
+    let part1: Rule = params.get("part1")?;
+    let part2: Rule = params.get("part2")?;
+    let mut input = solution.time("parse", || read_input(text, radix))?;
+    if params.get("merge")? {
+        input = solution.time("merge", || merged(&input));
+    }
+
+    let part1 = solution.time("part1", || solve(&input, &part1, radix.into()))?;
+    solution.answer("part1", part1);
+    let part2 = solution.time("part2", || solve(&input, &part2, radix.into()))?;
+    solution.answer("part2", part2);
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fuzz::Rng;
+
+    /// Whether `id` is invalid, from its digits one at a time.
+    fn scan_invalid(rule: &Rule, radix: u128, id: u128) -> bool {
+        let digits: Vec<u128> = std::iter::successors(Some(id), |&n| Some(n / radix))
+            .take_while(|&n| n > 0)
+            .map(|n| n % radix)
+            .collect();
+        rule.periods(digits.len() as u32).into_iter().any(|period| {
+            (period as usize..digits.len()).all(|i| digits[i] == digits[i - period as usize])
+        })
+    }
+
+    #[test]
+    fn tally_matches_a_scan() {
+        let rules = [
+            Rule::Exactly(2),
+            Rule::Exactly(3),
+            Rule::AtLeast(2),
+            Rule::AtLeast(3),
+            Rule::Blocks(vec![1, 2]),
+            Rule::Blocks(vec![2, 3]),
+        ];
+        let mut rng = Rng(0x4f1b_bcdc_bfa5_3e0b);
+
+        for radix in [2, 3, 7, 10, 16] {
+            for _ in 0..25 {
+                let low = rng.next() as u128 % 100_000;
+                let high = low + rng.next() as u128 % 2_000;
+                for rule in &rules {
+                    let invalid: Vec<u128> = (low..=high)
+                        .filter(|&id| scan_invalid(rule, radix, id))
+                        .collect();
+                    let expected = Tally {
+                        count: invalid.len() as u128,
+                        sum: Some(invalid.iter().sum()),
+                    };
+                    assert_eq!(
+                        tally_invalid(rule, radix, low, high),
+                        expected,
+                        "{rule:?} in base {radix} over {low}-{high}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn reports_near_the_top_of_u128() {
+        let repeated = 100000000000910000000000091000000000009;
+        assert_eq!(invalid_block(&Rule::AtLeast(2), 10, repeated), Some(13));
+        assert_eq!(invalid_block(&Rule::Exactly(2), 10, repeated), None);
+
+        let params = Params::with_defaults(DAY.params);
+        let text = format!(
+            "{repeated}-{repeated},{}-{}",
+            u128::MAX - 100_000,
+            u128::MAX
+        );
+        assert!(show_report(&text, &params).is_ok());
+    }
 }
@@ -1,48 +1,598 @@
-use std::fs;
+use std::{
+    fmt::{self, Display},
+    ops::AddAssign,
+    str::FromStr,
+};
 
-fn read_input() -> Vec<Vec<u64>> {
-    let input = fs::read_to_string("input/day3.txt").unwrap();
+use crate::error::{Error, Result};
+use crate::params::{Param, Params};
+use crate::runner::{Day, Solution, View};
 
+/// Digits go up to 'z', as with `char::to_digit`.
+const MAX_RADIX: usize = 36;
+
+/// Banks of batteries, one per line, with joltages written in `radix`.
+fn read_input(input: &str, radix: u32) -> Result<Vec<Vec<u8>>> {
     input
         .lines()
-        .map(|line| {
+        .enumerate()
+        .map(|(i, line)| {
             line.chars()
-                .map(|c| c.to_digit(10).unwrap() as u64)
-                .collect::<Vec<u64>>()
+                .map(|c| {
+                    c.to_digit(radix)
+                        .map(|d| d as u8)
+                        .ok_or_else(|| Error::at(i + 1, format!("'{c}' is not a joltage digit")))
+                })
+                .collect::<Result<Vec<u8>>>()
         })
         .collect()
 }
 
-fn left_max_index(data: &[u64]) -> usize {
-    let max_value = *data.iter().max().unwrap();
-    data.iter().position(|&x| x == max_value).unwrap()
+/// A number of any length, kept as digits in `radix`, least significant
+/// first, so that joltages of hundreds of digits add up exactly.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct BigNum {
+    radix: u8,
+    digits: Vec<u8>,
+}
+
+impl BigNum {
+    fn zero(radix: u8) -> Self {
+        Self {
+            radix,
+            digits: Vec::new(),
+        }
+    }
+
+    /// From digits written most significant first.
+    fn from_digits(digits: &[u8], radix: u8) -> Self {
+        Self {
+            radix,
+            digits: digits.iter().rev().copied().collect(),
+        }
+    }
+}
+
+impl AddAssign<&BigNum> for BigNum {
+    fn add_assign(&mut self, other: &BigNum) {
+        if self.digits.len() < other.digits.len() {
+            self.digits.resize(other.digits.len(), 0);
+        }
+
+        let mut carry = 0;
+        for (i, digit) in self.digits.iter_mut().enumerate() {
+            let sum = *digit + other.digits.get(i).copied().unwrap_or_default() + carry;
+            *digit = sum % self.radix;
+            carry = sum / self.radix;
+        }
+        if carry > 0 {
+            self.digits.push(carry);
+        }
+    }
+}
+
+impl Display for BigNum {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let digits: String = self
+            .digits
+            .iter()
+            .rev()
+            .skip_while(|&&d| d == 0)
+            .filter_map(|&d| char::from_digit(d.into(), self.radix.into()))
+            .collect();
+
+        if digits.is_empty() {
+            f.pad("0")
+        } else {
+            f.pad(&digits)
+        }
+    }
+}
+
+/// Batteries picked from a bank and the joltage digits they make.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Selection {
+    digits: Vec<u8>,
+    indices: Vec<usize>,
+}
+
+/// Picks `count` of `line` in a single pass: each battery evicts the ones
+/// before it that `evicts` says it beats, as long as enough are left to fill
+/// all `count`.
+fn stack_select(line: &[u8], count: usize, evicts: impl Fn(u8, u8) -> bool) -> Vec<usize> {
+    let mut stack: Vec<usize> = Vec::with_capacity(line.len());
+    let mut drops = line.len() - count;
+
+    for (i, &digit) in line.iter().enumerate() {
+        while drops > 0 && stack.last().is_some_and(|&top| evicts(digit, line[top])) {
+            stack.pop();
+            drops -= 1;
+        }
+        stack.push(i);
+    }
+    stack.truncate(count);
+    stack
+}
+
+/// The largest `count` digits of `line` in their original order.
+fn select_max(line: &[u8], count: usize) -> Selection {
+    let indices = stack_select(line, count, |digit, top| digit > top);
+    Selection {
+        digits: indices.iter().map(|&i| line[i]).collect(),
+        indices,
+    }
+}
+
+/// The smallest `count` digits of `line` in their original order that don't
+/// start with a zero: the leftmost smallest non-zero digit with room after it
+/// for the rest, then the smallest of what follows. `None` if every digit
+/// that could go first is a zero.
+fn select_min(line: &[u8], count: usize) -> Option<Selection> {
+    let mut indices = Vec::with_capacity(count);
+    if count > 0 {
+        let first = (0..=line.len() - count)
+            .filter(|&i| line[i] != 0)
+            .min_by_key(|&i| line[i])?;
+        indices.push(first);
+
+        let rest = &line[first + 1..];
+        let after = stack_select(rest, count - 1, |digit, top| digit < top);
+        indices.extend(after.into_iter().map(|i| first + 1 + i));
+    }
+
+    Some(Selection {
+        digits: indices.iter().map(|&i| line[i]).collect(),
+        indices,
+    })
+}
+
+/// What to aim for when picking batteries, beyond the largest joltage.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Objective {
+    Largest,
+    /// The smallest joltage that doesn't start with a zero.
+    Smallest,
+    /// The largest joltage that is a multiple of this.
+    DivisibleBy(u64),
+    /// The largest joltage using at most this many different digits.
+    Distinct(usize),
+}
+
+impl FromStr for Objective {
+    type Err = Error;
+
+    /// `largest`, `smallest`, `divisible:M` or `distinct:D`.
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = || {
+            Error::new(format!(
+                "invalid objective '{s}', expected 'largest', 'smallest', \
+                 'divisible:M' or 'distinct:D'"
+            ))
+        };
+
+        match s.split_once(':') {
+            None if s == "largest" => Ok(Objective::Largest),
+            None if s == "smallest" => Ok(Objective::Smallest),
+            Some(("divisible", m)) => match m.parse() {
+                Ok(0) | Err(_) => Err(invalid()),
+                Ok(m) => Ok(Objective::DivisibleBy(m)),
+            },
+            Some(("distinct", d)) => d.parse().map(Objective::Distinct).map_err(|_| invalid()),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+/// For every suffix of a bank and length, the remainders modulo `modulus`
+/// that some subsequence of that length can leave.
+struct Remainders {
+    modulus: u64,
+    count: usize,
+    /// `radix^j % modulus` for every length `j`
+    power: Vec<u64>,
+    reachable: Vec<bool>,
+}
+
+impl Remainders {
+    fn new(line: &[u8], count: usize, modulus: u64, radix: u8) -> Self {
+        let mut power = vec![1 % modulus; count + 1];
+        for j in 1..=count {
+            power[j] = mul_mod(power[j - 1], radix.into(), modulus);
+        }
+
+        let mut table = Self {
+            modulus,
+            count,
+            power,
+            reachable: vec![false; (line.len() + 1) * (count + 1) * modulus as usize],
+        };
+
+        for i in (0..=line.len()).rev() {
+            let at = table.index(i, 0, 0);
+            table.reachable[at] = true;
+            if i == line.len() {
+                continue;
+            }
+
+            for j in 1..=count {
+                let head = mul_mod(u64::from(line[i]), table.power[j - 1], modulus);
+                for r in 0..modulus {
+                    // skip line[i], or put it in front of a shorter subsequence
+                    let reachable = table.contains(i + 1, j, r)
+                        || table.contains(i + 1, j - 1, (r + modulus - head) % modulus);
+                    let at = table.index(i, j, r);
+                    table.reachable[at] = reachable;
+                }
+            }
+        }
+
+        table
+    }
+
+    fn index(&self, from: usize, length: usize, remainder: u64) -> usize {
+        (from * (self.count + 1) + length) * self.modulus as usize + remainder as usize
+    }
+
+    fn contains(&self, from: usize, length: usize, remainder: u64) -> bool {
+        self.reachable[self.index(from, length, remainder)]
+    }
+}
+
+fn mul_mod(a: u64, b: u64, modulus: u64) -> u64 {
+    (u128::from(a) * u128::from(b) % u128::from(modulus)) as u64
 }
 
-fn bank_power(line: &Vec<u64>, count: usize) -> u64 {
-    let mut out = 0;
-    let mut idx = 0;
+/// Picks `count` batteries for `objective`. The largest and smallest come
+/// straight from the stack; the others are picked one at a time, taking the
+/// best digit whose earliest occurrence still leaves a valid way to finish,
+/// which the tables built up front answer in constant time per candidate.
+/// Returns `None` if no selection meets the objective.
+fn select(line: &[u8], count: usize, objective: Objective, radix: u8) -> Option<Selection> {
+    if count > line.len() {
+        return None;
+    }
+    match objective {
+        Objective::Largest => return Some(select_max(line, count)),
+        Objective::Smallest => return select_min(line, count),
+        Objective::DivisibleBy(_) | Objective::Distinct(_) => {}
+    }
+
+    let remainders = match objective {
+        Objective::DivisibleBy(m) => Some(Remainders::new(line, count, m, radix)),
+        _ => None,
+    };
+    // how many of each digit are left from every position on
+    let mut left_over = vec![[0usize; MAX_RADIX]; line.len() + 1];
+    for i in (0..line.len()).rev() {
+        left_over[i] = left_over[i + 1];
+        left_over[i][line[i] as usize] += 1;
+    }
+
+    let mut indices = Vec::with_capacity(count);
+    let mut start = 0;
+    let mut remainder = 0;
+    let mut used = [false; MAX_RADIX];
+
+    for picked in 0..count {
+        let rest = count - picked - 1;
+        let (position, digit) = (0..radix).rev().find_map(|digit| {
+            let position = start + line[start..].iter().position(|&d| d == digit)?;
+            let after = position + 1;
+            if line.len() - after < rest {
+                return None;
+            }
+
+            let finishes = match objective {
+                Objective::Largest | Objective::Smallest => unreachable!(),
+                Objective::DivisibleBy(m) => {
+                    let remainders = remainders.as_ref().unwrap();
+                    let head = (remainder * u64::from(radix) + u64::from(digit)) % m;
+                    let shifted = mul_mod(head, remainders.power[rest], m);
+                    remainders.contains(after, rest, (m - shifted) % m)
+                }
+                Objective::Distinct(limit) => {
+                    let mut used = used;
+                    used[digit as usize] = true;
+                    let new = limit.checked_sub(used.iter().filter(|&&u| u).count());
+
+                    // digits already used are free, then the most common others
+                    let counts = &left_over[after];
+                    let mut others: Vec<usize> = (0..radix as usize)
+                        .filter(|&d| !used[d])
+                        .map(|d| counts[d])
+                        .collect();
+                    others.sort_unstable_by(|a, b| b.cmp(a));
+                    new.is_some_and(|new| {
+                        let free: usize = (0..radix as usize)
+                            .filter(|&d| used[d])
+                            .map(|d| counts[d])
+                            .sum();
+                        free + others.iter().take(new).sum::<usize>() >= rest
+                    })
+                }
+            };
+            finishes.then_some((position, digit))
+        })?;
+
+        indices.push(position);
+        start = position + 1;
+        if let Objective::DivisibleBy(m) = objective {
+            remainder = (remainder * u64::from(radix) + u64::from(digit)) % m;
+        }
+        used[digit as usize] = true;
+    }
+
+    Some(Selection {
+        digits: indices.iter().map(|&i| line[i]).collect(),
+        indices,
+    })
+}
+
+/// Cells a `Remainders` table may have before it is refused, 256MB.
+const MAX_TABLE: usize = 1 << 28;
+
+/// The batteries chosen from one bank, or an error naming its line.
+fn pick(
+    bank: &[u8],
+    line: usize,
+    count: usize,
+    objective: Objective,
+    radix: u8,
+) -> Result<Selection> {
+    match objective {
+        Objective::DivisibleBy(m)
+            if usize::try_from(m)
+                .ok()
+                .and_then(|m| m.checked_mul((bank.len() + 1) * (count + 1)))
+                .is_none_or(|cells| cells > MAX_TABLE) =>
+        {
+            Err(Error::at(
+                line,
+                format!("divisor {m} is too large for this bank"),
+            ))
+        }
+        _ => select(bank, count, objective, radix)
+            .ok_or_else(|| Error::at(line, format!("no {count} batteries meet the objective"))),
+    }
+}
+
+fn solve(input: &[Vec<u8>], count: usize, objective: Objective, radix: u8) -> Result<BigNum> {
+    let mut total = BigNum::zero(radix);
+    for (i, bank) in input.iter().enumerate() {
+        let selection = pick(bank, i + 1, count, objective, radix)?;
+        total += &BigNum::from_digits(&selection.digits, radix);
+    }
+    Ok(total)
+}
+
+fn radix_from(params: &Params) -> Result<u8> {
+    let radix: u8 = params.get("radix")?;
+    if !(2..=MAX_RADIX as u8).contains(&radix) {
+        return Err(Error::new("radix must be between 2 and 36"));
+    }
+    Ok(radix)
+}
+
+/// The bank with only the chosen batteries showing.
+fn marked(bank: &[u8], selection: &Selection, radix: u8) -> String {
+    bank.iter()
+        .enumerate()
+        .map(|(i, &d)| {
+            if selection.indices.contains(&i) {
+                char::from_digit(d.into(), radix.into()).unwrap_or('?')
+            } else {
+                '.'
+            }
+        })
+        .collect()
+}
 
-    for i in 0..count {
-        let reserve = line.len() - count + i + 1;
-        idx += left_max_index(&line[idx..reserve]);
-        out = out * 10 + line[idx];
+/// Every bank with the batteries each part turns on and the joltage they add.
+fn show_table(text: &str, params: &Params) -> Result<()> {
+    let radix = radix_from(params)?;
+    let objective: Objective = params.get("objective")?;
+    let counts = [
+        ("part1", params.get::<usize>("part1_digits")?),
+        ("part2", params.get::<usize>("part2_digits")?),
+    ];
+    let input = read_input(text, radix.into())?;
 
-        idx += 1
+    let mut totals = [BigNum::zero(radix), BigNum::zero(radix)];
+    for (i, bank) in input.iter().enumerate() {
+        println!("line {}:", i + 1);
+        for ((label, count), total) in counts.iter().zip(totals.iter_mut()) {
+            if bank.len() < *count {
+                println!("  {label}: fewer than {count} batteries");
+                continue;
+            }
+            let selection = pick(bank, i + 1, *count, objective, radix)?;
+            let value = BigNum::from_digits(&selection.digits, radix);
+            println!("  {label}: {}  {value}", marked(bank, &selection, radix));
+            *total += &value;
+        }
     }
-    out
+    println!("total: part1 {}, part2 {}", totals[0], totals[1]);
+
+    Ok(())
 }
 
-fn solve(input: &Vec<Vec<u64>>, count: usize) -> u64 {
-    input.iter().map(|x| bank_power(x, count)).sum()
+pub const DAY: Day = Day {
+    number: 3,
+    params: &[
+        Param::new(
+            "part1_digits",
+            "2",
+            "batteries turned on per bank in part 1",
+        ),
+        Param::new(
+            "part2_digits",
+            "12",
+            "batteries turned on per bank in part 2",
+        ),
+        Param::new(
+            "objective",
+            "largest",
+            "largest, smallest, divisible:M or distinct:D joltage per bank",
+        ),
+        Param::new("radix", "10", "base the joltages are written in, 2 to 36"),
+    ],
+    parse: |text| read_input(text, 10).map(drop),
+    run,
+    visualize: None,
+    views: &[View {
+        name: "table",
+        help: "batteries chosen from each bank and the joltage they add",
+        show: show_table,
+    }],
+};
+
+fn run(text: &str, params: &Params, solution: &mut Solution) -> Result<()> {
+    let part1_digits: usize = params.get("part1_digits")?;
+    let part2_digits: usize = params.get("part2_digits")?;
+    let objective: Objective = params.get("objective")?;
+    let radix = radix_from(params)?;
+    let input = solution.time("parse", || read_input(text, radix.into()))?;
+
+    let needed = part1_digits.max(part2_digits);
+    if let Some(i) = input.iter().position(|bank| bank.len() < needed) {
+        return Err(Error::at(
+            i + 1,
+            format!("bank has fewer than {needed} batteries"),
+        ));
+    }
+
+    let part1 = solution.time("part1", || solve(&input, part1_digits, objective, radix))?;
+    solution.answer("part1", part1);
+    let part2 = solution.time("part2", || solve(&input, part2_digits, objective, radix))?;
+    solution.answer("part2", part2);
+
+    Ok(())
 }
 
-pub fn run() {
-    println!("\n--- day3 ---");
-    let input = read_input();
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
 
-    let result1 = solve(&input, 2);
-    println!("part1: {}", result1);
+    use super::*;
+    use crate::fuzz::Rng;
+
+    fn left_max_index(data: &[u8]) -> usize {
+        let max_value = *data.iter().max().unwrap();
+        data.iter().position(|&x| x == max_value).unwrap()
+    }
+
+    /// The largest `count` digits of `line` in their original order, picked one
+    /// at a time as the leftmost maximum that still leaves room for the rest.
+    fn bank_power(line: &[u8], count: usize) -> Vec<u8> {
+        let mut out = Vec::with_capacity(count);
+        let mut idx = 0;
+
+        for i in 0..count {
+            let reserve = line.len() - count + i + 1;
+            idx += left_max_index(&line[idx..reserve]);
+            out.push(line[idx]);
+
+            idx += 1
+        }
+        out
+    }
 
-    let result2 = solve(&input, 12);
-    println!("part2: {}", result2);
+    fn banks(seed: u64) -> impl Iterator<Item = Vec<u8>> {
+        let mut rng = Rng(seed);
+        let mut next = move || rng.next();
+        std::iter::repeat_with(move || {
+            let length = next() % 40 + 1;
+            // a narrow digit range some of the time, so that ties are common
+            let digits = if next() % 2 == 0 { 3 } else { 9 };
+            (0..length).map(|_| (next() % digits) as u8 + 1).collect()
+        })
+    }
+
+    #[test]
+    fn stack_matches_greedy() {
+        for bank in banks(0x9e37_79b9_7f4a_7c15).take(2000) {
+            for count in 1..=bank.len() {
+                let greedy = bank_power(&bank, count);
+                let selection = select_max(&bank, count);
+                assert_eq!(selection.digits, greedy, "{count} of {bank:?}");
+
+                assert!(
+                    selection.indices.windows(2).all(|w| w[0] < w[1]),
+                    "indices of {count} of {bank:?} are not increasing"
+                );
+                let picked: Vec<u8> = selection.indices.iter().map(|&i| bank[i]).collect();
+                assert_eq!(picked, selection.digits, "indices of {count} of {bank:?}");
+
+                assert_eq!(
+                    select(&bank, count, Objective::Largest, 10).map(|s| s.digits),
+                    Some(greedy),
+                    "table selection of {count} of {bank:?}"
+                );
+            }
+        }
+    }
+
+    /// The best selection for `objective`, out of every possible one.
+    fn exhaustive(bank: &[u8], count: usize, objective: Objective, radix: u8) -> Option<Vec<u8>> {
+        let value = |digits: &[u8]| {
+            digits
+                .iter()
+                .fold(0u64, |n, &d| n * u64::from(radix) + u64::from(d))
+        };
+        let candidates = (0..bank.len())
+            .combinations(count)
+            .map(|indices| indices.iter().map(|&i| bank[i]).collect::<Vec<u8>>());
+
+        match objective {
+            Objective::Largest => candidates.max(),
+            Objective::Smallest => candidates.filter(|digits| digits[0] != 0).min(),
+            Objective::DivisibleBy(m) => candidates
+                .filter(|digits| value(digits).is_multiple_of(m))
+                .max(),
+            Objective::Distinct(limit) => candidates
+                .filter(|digits| digits.iter().unique().count() <= limit)
+                .max(),
+        }
+    }
+
+    #[test]
+    fn select_matches_exhaustive_search() {
+        let objectives = [
+            Objective::Largest,
+            Objective::Smallest,
+            Objective::DivisibleBy(3),
+            Objective::DivisibleBy(7),
+            Objective::DivisibleBy(12),
+            Objective::Distinct(1),
+            Objective::Distinct(2),
+        ];
+        let mut rng = Rng(0x6a09_e667_f3bc_c908);
+
+        for _ in 0..300 {
+            let radix = [2, 3, 10][rng.below(3)];
+            let bank: Vec<u8> = (0..rng.below(10) + 1)
+                .map(|_| rng.below(radix as usize) as u8)
+                .collect();
+            for count in 1..=bank.len() {
+                for objective in objectives {
+                    let selection = select(&bank, count, objective, radix);
+                    assert_eq!(
+                        selection.as_ref().map(|s| s.digits.clone()),
+                        exhaustive(&bank, count, objective, radix),
+                        "{objective:?} for {count} of {bank:?} in base {radix}"
+                    );
+                    if let Some(selection) = selection {
+                        assert!(selection.indices.windows(2).all(|w| w[0] < w[1]));
+                        assert!(
+                            selection
+                                .indices
+                                .iter()
+                                .zip(&selection.digits)
+                                .all(|(&i, &d)| bank[i] == d)
+                        );
+                    }
+                }
+            }
+        }
+    }
 }
@@ -1,89 +1,666 @@
+use std::{fs, path::PathBuf, str::FromStr};
+
 use itertools::iproduct;
-use std::fs;
+
+use crate::error::{Error, Result};
+use crate::params::{Param, Params};
+use crate::runner::{Day, Solution, View};
+use crate::svg;
+
+/// What the floor plan says about a cell, whatever is on it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Mask {
+    Free,
+    /// A pillar or the like: never holds a roll, but crowds the rolls
+    /// around it like one.
+    Blocked,
+    /// A roll here is never taken away.
+    Unremovable,
+}
 
 #[derive(Debug)]
 struct Puzzle {
     map: Vec<bool>,
+    mask: Vec<Mask>,
     width: i32,
     height: i32,
+    /// Whether the edges wrap around, making the floor a torus.
+    wrap: bool,
 }
 
-impl Puzzle {
-    fn read_input(file: &str) -> Self {
-        let input_text = fs::read_to_string(file).unwrap();
-
-        let map: Vec<bool> = input_text
-            .chars()
-            .filter(|&c| c != '\n')
-            .map(|c| match c {
-                '@' => true,
-                _ => false,
+/// What a character in the grid stands for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Symbol {
+    Empty,
+    Roll,
+    /// A blocked cell, see `Mask::Blocked`.
+    Wall,
+    /// A roll that is never taken away.
+    Fixed,
+}
+
+const DEFAULT_SYMBOLS: &str = "@=roll,.=empty";
+
+/// The characters a grid may use, e.g. `@=roll,.=empty,#=wall,!=fixed`.
+#[derive(Debug, Clone)]
+struct Symbols(Vec<(char, Symbol)>);
+
+impl Symbols {
+    fn get(&self, c: char) -> Option<Symbol> {
+        self.0
+            .iter()
+            .find(|&&(s, _)| s == c)
+            .map(|&(_, symbol)| symbol)
+    }
+}
+
+impl FromStr for Symbols {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        s.split(',')
+            .map(|entry| {
+                let invalid = || Error::new(format!("invalid symbol '{entry}'"));
+                let (c, meaning) = entry.split_once('=').ok_or_else(invalid)?;
+                let mut chars = c.chars();
+                let (Some(c), None) = (chars.next(), chars.next()) else {
+                    return Err(invalid());
+                };
+
+                let symbol = match meaning.trim() {
+                    "empty" => Symbol::Empty,
+                    "roll" => Symbol::Roll,
+                    "wall" => Symbol::Wall,
+                    "fixed" => Symbol::Fixed,
+                    _ => return Err(invalid()),
+                };
+                Ok((c, symbol))
             })
-            .collect();
+            .collect::<Result<_>>()
+            .map(Symbols)
+    }
+}
 
-        let width = input_text.chars().position(|x| x == '\n').unwrap();
-        let height = map.len() / width;
+impl Puzzle {
+    /// Reads the grid a line at a time, with either line ending. Every row
+    /// must be as wide as the first.
+    fn read_input(input_text: &str, symbols: &Symbols) -> Result<Self> {
+        let mut map = Vec::new();
+        let mut mask = Vec::new();
+        let mut width = 0;
+        let mut height = 0;
+
+        for (i, line) in input_text
+            .trim_end_matches(['\r', '\n'])
+            .lines()
+            .enumerate()
+        {
+            let row = line.chars().count();
+            if i == 0 {
+                width = row;
+            } else if row != width {
+                return Err(Error::at(
+                    i + 1,
+                    format!("row is {row} cells wide, the first is {width}"),
+                ));
+            }
+
+            for c in line.chars() {
+                let symbol = symbols
+                    .get(c)
+                    .ok_or_else(|| Error::at(i + 1, format!("unknown symbol '{c}'")))?;
+                map.push(matches!(symbol, Symbol::Roll | Symbol::Fixed));
+                mask.push(match symbol {
+                    Symbol::Empty | Symbol::Roll => Mask::Free,
+                    Symbol::Wall => Mask::Blocked,
+                    Symbol::Fixed => Mask::Unremovable,
+                });
+            }
+            height += 1;
+        }
 
-        Self {
+        if width == 0 {
+            return Err(Error::at(1, "empty grid row"));
+        }
+
+        Ok(Self {
             map,
+            mask,
             width: width as i32,
-            height: height as i32,
+            height,
+            wrap: false,
+        })
+    }
+
+    /// Applies a mask of the same size as the grid: '#' marks blocked cells
+    /// and '!' rolls that must stay, anything else leaves a cell alone.
+    fn apply_mask(&mut self, text: &str) -> Result<()> {
+        let rows: Vec<&str> = text.lines().collect();
+        if rows.len() != self.height as usize {
+            return Err(Error::new(format!(
+                "mask has {} rows but the grid has {}",
+                rows.len(),
+                self.height
+            )));
+        }
+
+        for (y, row) in rows.iter().enumerate() {
+            if row.chars().count() != self.width as usize {
+                return Err(Error::at(
+                    y + 1,
+                    format!("mask row is not {} cells wide", self.width),
+                ));
+            }
+            for (x, c) in row.chars().enumerate() {
+                let i = y * self.width as usize + x;
+                match c {
+                    '#' => {
+                        self.mask[i] = Mask::Blocked;
+                        self.map[i] = false;
+                    }
+                    '!' if self.map[i] => self.mask[i] = Mask::Unremovable,
+                    _ => {}
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// `(x, y)` brought back onto the grid if the edges wrap.
+    fn wrap_point(&self, x: i32, y: i32) -> (i32, i32) {
+        if self.wrap {
+            (x.rem_euclid(self.width), y.rem_euclid(self.height))
+        } else {
+            (x, y)
         }
     }
 
+    /// Whether `(x, y)` crowds the rolls around it, by holding a roll or
+    /// being blocked. Off the grid is empty unless the edges wrap.
     fn get_at(&self, x: i32, y: i32) -> bool {
-        x >= 0
-            && y >= 0
-            && x < self.width
-            && y < self.height
-            && self.map[(y * self.width + x) as usize]
+        let (x, y) = self.wrap_point(x, y);
+        x >= 0 && y >= 0 && x < self.width && y < self.height && {
+            let i = (y * self.width + x) as usize;
+            self.map[i] || self.mask[i] == Mask::Blocked
+        }
     }
 
-    fn set_at(&mut self, x: i32, y: i32, value: bool) {
-        self.map[(y * self.width + x) as usize] = value
+    /// Whether `(x, y)`, on the grid, holds a roll that may be taken away.
+    fn movable(&self, x: i32, y: i32) -> bool {
+        let i = (y * self.width + x) as usize;
+        self.map[i] && self.mask[i] == Mask::Free
     }
 
-    fn iter_removable(&self) -> impl Iterator<Item = (i32, i32)> {
+    /// Rolls that `rules` allow a forklift to reach.
+    fn iter_removable<'a>(&'a self, rules: &'a Rules) -> impl Iterator<Item = (i32, i32)> + 'a {
         iproduct!(0..self.width, 0..self.height)
+            .filter(|&(x, y)| self.movable(x, y))
+            .filter(|&(x, y)| self.neighbours(x, y, &rules.stencil).count() < rules.threshold)
+    }
+
+    /// Rolls and blocked cells at `stencil` offsets from `(x, y)`.
+    fn neighbours<'a>(
+        &'a self,
+        x: i32,
+        y: i32,
+        stencil: &'a Stencil,
+    ) -> impl Iterator<Item = (i32, i32)> + 'a {
+        stencil
+            .0
+            .iter()
+            .map(move |&(ox, oy)| self.wrap_point(x + ox, y + oy))
             .filter(|&(x, y)| self.get_at(x, y))
-            .map(|(x, y)| {
-                let count = iproduct!(-1..=1, -1..=1)
-                    .filter(|&(ox, oy)| self.get_at(x + ox, y + oy))
-                    .count();
+    }
+
+    /// Rolls that have `(x, y)` among their neighbours, which differ from
+    /// its own neighbours when the stencil isn't symmetric.
+    fn neighbours_of<'a>(
+        &'a self,
+        x: i32,
+        y: i32,
+        stencil: &'a Stencil,
+    ) -> impl Iterator<Item = (i32, i32)> + 'a {
+        stencil
+            .0
+            .iter()
+            .map(move |&(ox, oy)| self.wrap_point(x - ox, y - oy))
+            .filter(|&(x, y)| self.get_at(x, y))
+    }
+
+    /// The round each roll is removed in when removable rolls are taken away
+    /// all at once, round after round, or `None` for rolls that stay.
+    ///
+    /// Neighbour counts are kept up to date as rolls go, so only the
+    /// neighbours of removed rolls are looked at again.
+    fn peel(&self, rules: &Rules) -> Vec<Option<u32>> {
+        let index = |x: i32, y: i32| (y * self.width + x) as usize;
+        let mut round = vec![None; self.map.len()];
+        let mut counts = vec![0; self.map.len()];
+        let mut current = Vec::new();
+
+        for (y, x) in iproduct!(0..self.height, 0..self.width) {
+            if self.get_at(x, y) {
+                counts[index(x, y)] = self.neighbours(x, y, &rules.stencil).count();
+                if self.movable(x, y) && counts[index(x, y)] < rules.threshold {
+                    round[index(x, y)] = Some(1);
+                    current.push((x, y));
+                }
+            }
+        }
 
-                (x, y, count)
+        let mut number = 1;
+        while !current.is_empty() {
+            number += 1;
+            let mut next = Vec::new();
+
+            for &(x, y) in &current {
+                for (nx, ny) in self.neighbours_of(x, y, &rules.stencil) {
+                    let i = index(nx, ny);
+                    counts[i] -= 1;
+                    if round[i].is_none() && self.movable(nx, ny) && counts[i] < rules.threshold {
+                        round[i] = Some(number);
+                        next.push((nx, ny));
+                    }
+                }
+            }
+
+            current = next;
+        }
+
+        round
+    }
+}
+
+/// Offsets from a roll to the cells counted as its neighbours.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Stencil(Vec<(i32, i32)>);
+
+impl FromStr for Stencil {
+    type Err = Error;
+
+    /// `moore[:R]`, `von-neumann[:R]` or `offsets:DX,DY;DX,DY;...`.
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = || {
+            Error::new(format!(
+                "invalid neighbourhood '{s}', expected 'moore[:R]', \
+                 'von-neumann[:R]' or 'offsets:DX,DY;...'"
+            ))
+        };
+        let (kind, value) = s.split_once(':').unwrap_or((s, "1"));
+        let radius = || value.trim().parse::<i32>().map_err(|_| invalid());
+
+        let mut offsets: Vec<(i32, i32)> = match kind.trim() {
+            "moore" => {
+                let r = radius()?;
+                iproduct!(-r..=r, -r..=r).collect()
+            }
+            "von-neumann" => {
+                let r = radius()?;
+                iproduct!(-r..=r, -r..=r)
+                    .filter(|&(x, y)| x.abs() + y.abs() <= r)
+                    .collect()
+            }
+            "offsets" => value
+                .split(';')
+                .map(|pair| {
+                    let (x, y) = pair.split_once(',').ok_or_else(invalid)?;
+                    Ok((
+                        x.trim().parse().map_err(|_| invalid())?,
+                        y.trim().parse().map_err(|_| invalid())?,
+                    ))
+                })
+                .collect::<Result<_>>()?,
+            _ => return Err(invalid()),
+        };
+
+        // a roll is never its own neighbour
+        offsets.retain(|&offset| offset != (0, 0));
+        offsets.sort();
+        offsets.dedup();
+        Ok(Stencil(offsets))
+    }
+}
+
+/// When a forklift can reach a roll: fewer than `threshold` of the cells
+/// in its `stencil` hold rolls.
+#[derive(Debug, Clone)]
+struct Rules {
+    stencil: Stencil,
+    threshold: usize,
+}
+
+fn rules_from(params: &Params) -> Result<Rules> {
+    Ok(Rules {
+        stencil: params.get("neighbourhood")?,
+        threshold: params.get("threshold")?,
+    })
+}
+
+/// The grid with the wrap and mask options applied.
+fn puzzle_from(text: &str, params: &Params) -> Result<Puzzle> {
+    let mut puzzle = Puzzle::read_input(text, &params.get("symbols")?)?;
+    puzzle.wrap = params.get("wrap")?;
+
+    let mask: String = params.get("mask")?;
+    if !mask.is_empty() {
+        let text = fs::read_to_string(&mask)
+            .map_err(|err| Error::new(format!("could not read {mask}: {err}")))?;
+        puzzle.apply_mask(&text)?;
+    }
+
+    Ok(puzzle)
+}
+
+fn part1(puzzle: &Puzzle, rules: &Rules) -> usize {
+    puzzle.iter_removable(rules).count()
+}
+
+fn part2(puzzle: &Puzzle, rules: &Rules) -> usize {
+    puzzle.peel(rules).iter().flatten().count()
+}
+
+/// Rolls in grey, with the ones removable in the first round highlighted.
+fn visualize(text: &str, params: &Params) -> Result<String> {
+    let rules = rules_from(params)?;
+    let puzzle = puzzle_from(text, params)?;
+
+    let cells = |mask: Mask, roll: bool| -> Vec<(i64, i64)> {
+        iproduct!(0..puzzle.width, 0..puzzle.height)
+            .filter(|&(x, y)| {
+                let i = (y * puzzle.width + x) as usize;
+                puzzle.mask[i] == mask && puzzle.map[i] == roll
             })
-            .filter(|&(_, _, count)| count <= 4)
-            .map(|(x, y, _)| (x, y))
+            .map(|(x, y)| (x as i64, y as i64))
+            .collect()
+    };
+    let removable: Vec<(i64, i64)> = puzzle
+        .iter_removable(&rules)
+        .map(|(x, y)| (x as i64, y as i64))
+        .collect();
+
+    Ok(svg::grid(
+        puzzle.width as i64,
+        puzzle.height as i64,
+        &[
+            ("#666", cells(Mask::Free, true)),
+            ("#2a4f8a", cells(Mask::Unremovable, true)),
+            ("#222", cells(Mask::Blocked, false)),
+            ("#e0904a", removable),
+        ],
+    ))
+}
+
+/// Character for a roll removed in `round`: 1-9, then a-z, then '+'.
+fn round_symbol(round: u32) -> char {
+    match round {
+        1..=9 => char::from_digit(round, 10).unwrap(),
+        10..=35 => char::from_digit(round, 36).unwrap(),
+        _ => '+',
     }
 }
 
-fn part2(puzzle: &mut Puzzle) -> usize {
-    let mut total = 0;
+/// Every roll marked with the round it is removed in, '@' if never.
+fn show_layers(text: &str, params: &Params) -> Result<()> {
+    let rules = rules_from(params)?;
+    let puzzle = puzzle_from(text, params)?;
+    let rounds = puzzle.peel(&rules);
 
-    loop {
-        let removable: Vec<_> = puzzle.iter_removable().collect();
+    for y in 0..puzzle.height {
+        let row: String = (0..puzzle.width)
+            .map(|x| {
+                let i = (y * puzzle.width + x) as usize;
+                match (puzzle.mask[i], puzzle.map[i], rounds[i]) {
+                    (Mask::Blocked, _, _) => '#',
+                    (_, false, _) => '.',
+                    (_, true, None) => '@',
+                    (_, true, Some(round)) => round_symbol(round),
+                }
+            })
+            .collect();
+        println!("{row}");
+    }
 
-        if removable.is_empty() {
-            break;
+    let last = rounds.iter().flatten().max().copied().unwrap_or_default();
+    println!(
+        "{} rolls removed in {last} rounds, {} left",
+        rounds.iter().flatten().count(),
+        puzzle.map.iter().filter(|&&roll| roll).count() - rounds.iter().flatten().count()
+    );
+
+    Ok(())
+}
+
+/// What a cell looks like after a round of removals.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Cell {
+    Empty,
+    Blocked,
+    Roll,
+    /// Removed in the round just shown.
+    Removed,
+    /// Removed in an earlier round.
+    Gone,
+}
+
+impl Cell {
+    fn symbol(self) -> char {
+        match self {
+            Cell::Empty => '.',
+            Cell::Blocked => '#',
+            Cell::Roll => '@',
+            Cell::Removed => 'x',
+            Cell::Gone => ' ',
         }
-        total += removable.len();
+    }
 
-        for (x, y) in removable {
-            puzzle.set_at(x, y, false);
+    fn rgb(self) -> [u8; 3] {
+        match self {
+            Cell::Empty => [0xff, 0xff, 0xff],
+            Cell::Blocked => [0x22, 0x22, 0x22],
+            Cell::Roll => [0x66, 0x66, 0x66],
+            Cell::Removed => [0xe0, 0x90, 0x4a],
+            Cell::Gone => [0xf2, 0xe0, 0xcc],
         }
     }
+}
+
+/// Pixels per cell in PPM frames.
+const FRAME_SCALE: usize = 4;
+
+/// Writes the grid after every round, starting from the untouched grid, as
+/// numbered text or PPM files in `frame_dir`.
+fn show_frames(text: &str, params: &Params) -> Result<()> {
+    let rules = rules_from(params)?;
+    let format: String = params.get("frame_format")?;
+    if format != "text" && format != "ppm" {
+        return Err(Error::new(format!(
+            "unknown frame format '{format}', expected 'text' or 'ppm'"
+        )));
+    }
+    let dir = PathBuf::from(params.get::<String>("frame_dir")?);
+    let puzzle = puzzle_from(text, params)?;
+    let rounds = puzzle.peel(&rules);
+    let last = rounds.iter().flatten().max().copied().unwrap_or_default();
+
+    fs::create_dir_all(&dir)
+        .map_err(|err| Error::new(format!("could not create {}: {err}", dir.display())))?;
+
+    for frame in 0..=last {
+        let cells: Vec<Cell> = (puzzle.map.iter().zip(puzzle.mask.iter()))
+            .zip(rounds.iter())
+            .map(|((&roll, &mask), &round)| match round {
+                _ if mask == Mask::Blocked => Cell::Blocked,
+                _ if !roll => Cell::Empty,
+                Some(round) if round == frame => Cell::Removed,
+                Some(round) if round < frame => Cell::Gone,
+                _ => Cell::Roll,
+            })
+            .collect();
+        let rows = cells.chunks(puzzle.width as usize);
+
+        let (path, data) = if format == "text" {
+            let text: String = rows
+                .flat_map(|row| row.iter().map(|cell| cell.symbol()).chain(['\n']))
+                .collect();
+            (dir.join(format!("round_{frame:03}.txt")), text.into_bytes())
+        } else {
+            let width = puzzle.width as usize * FRAME_SCALE;
+            let height = puzzle.height as usize * FRAME_SCALE;
+            let mut data = format!("P6\n{width} {height}\n255\n").into_bytes();
+            for row in rows {
+                let line: Vec<u8> = row
+                    .iter()
+                    .flat_map(|cell| cell.rgb().repeat(FRAME_SCALE))
+                    .collect();
+                for _ in 0..FRAME_SCALE {
+                    data.extend_from_slice(&line);
+                }
+            }
+            (dir.join(format!("round_{frame:03}.ppm")), data)
+        };
+
+        fs::write(&path, data)
+            .map_err(|err| Error::new(format!("could not write {}: {err}", path.display())))?;
+    }
+
+    println!("wrote {} frames to {}", last + 1, dir.display());
+    Ok(())
+}
 
-    total
+pub const DAY: Day = Day {
+    number: 4,
+    params: &[
+        Param::new(
+            "threshold",
+            "4",
+            "rolls with fewer neighbouring rolls than this can be removed",
+        ),
+        Param::new(
+            "neighbourhood",
+            "moore",
+            "cells counted as neighbours: moore[:R], von-neumann[:R] or offsets:DX,DY;...",
+        ),
+        Param::new(
+            "frame_format",
+            "text",
+            "files written by the frames view: text or ppm",
+        ),
+        Param::new("frame_dir", "frames", "directory the frames view writes to"),
+        Param::new(
+            "symbols",
+            DEFAULT_SYMBOLS,
+            "grid characters, as C=empty, roll, wall or fixed separated by commas",
+        ),
+        Param::new(
+            "wrap",
+            "false",
+            "whether the grid wraps around at the edges",
+        ),
+        Param::new(
+            "mask",
+            "",
+            "file marking blocked ('#') and unremovable ('!') cells",
+        ),
+    ],
+    parse: |text| Puzzle::read_input(text, &DEFAULT_SYMBOLS.parse()?).map(drop),
+    run,
+    visualize: Some(visualize),
+    views: &[
+        View {
+            name: "layers",
+            help: "map of the round each roll is removed in",
+            show: show_layers,
+        },
+        View {
+            name: "frames",
+            help: "one text or PPM file per removal round",
+            show: show_frames,
+        },
+    ],
+};
+
+fn run(text: &str, params: &Params, solution: &mut Solution) -> Result<()> {
+    let rules = rules_from(params)?;
+    let puzzle = solution.time("parse", || puzzle_from(text, params))?;
+
+    solution.part("part1", || part1(&puzzle, &rules));
+    solution.part("part2", || part2(&puzzle, &rules));
+
+    Ok(())
 }
 
-pub fn run() {
-    println!("\n--- day4 ---");
-    let mut puzzle = Puzzle::read_input("input/day4.txt");
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fuzz::Rng;
+
+    /// The round each roll goes in, by rescanning the whole grid for
+    /// removable rolls every round and taking them all away.
+    fn rescan(puzzle: &Puzzle, rules: &Rules) -> Vec<Option<u32>> {
+        let mut puzzle = Puzzle {
+            map: puzzle.map.clone(),
+            mask: puzzle.mask.clone(),
+            ..*puzzle
+        };
+        let mut round = vec![None; puzzle.map.len()];
+        let mut number = 0;
 
-    let result1 = puzzle.iter_removable().count();
-    println!("part1: {}", result1);
+        loop {
+            let removable: Vec<_> = puzzle.iter_removable(rules).collect();
+            if removable.is_empty() {
+                break;
+            }
+            number += 1;
 
-    let result2 = part2(&mut puzzle);
-    println!("part2: {}", result2);
+            for (x, y) in removable {
+                let i = (y * puzzle.width + x) as usize;
+                puzzle.map[i] = false;
+                round[i] = Some(number);
+            }
+        }
+
+        round
+    }
+
+    #[test]
+    fn peel_matches_a_rescan() {
+        let symbols: Symbols = "@=roll,.=empty,#=wall,!=fixed".parse().unwrap();
+        let stencils = [
+            "moore",
+            "moore:2",
+            "von-neumann",
+            "von-neumann:2",
+            "offsets:1,0;0,1;1,1",
+            "offsets:2,1;-1,0;0,-3",
+        ];
+        let mut rng = Rng(0xbb67_ae85_84ca_a73b);
+
+        for _ in 0..300 {
+            let (width, height) = (rng.below(12) + 1, rng.below(12) + 1);
+            let text: String = (0..height)
+                .map(|_| {
+                    let row: String = (0..width)
+                        .map(|_| *rng.pick(&['@', '@', '@', '.', '.', '#', '!']))
+                        .collect();
+                    row + "\n"
+                })
+                .collect();
+
+            let mut puzzle = Puzzle::read_input(&text, &symbols).unwrap();
+            puzzle.wrap = rng.below(2) == 0;
+            let rules = Rules {
+                stencil: rng.pick(&stencils).parse().unwrap(),
+                threshold: rng.below(9) + 1,
+            };
+
+            let expected = rescan(&puzzle, &rules);
+            assert_eq!(puzzle.peel(&rules), expected, "{rules:?} on\n{text}");
+            assert_eq!(
+                part2(&puzzle, &rules),
+                expected.iter().flatten().count(),
+                "{rules:?} on\n{text}"
+            );
+        }
+    }
 }
@@ -1,4 +1,7 @@
-use std::fs;
+use crate::error::{Error, Result};
+use crate::interval::IntervalSet;
+use crate::params::Params;
+use crate::runner::{Day, Solution, View};
 
 #[derive(Debug)]
 struct Puzzle {
@@ -7,92 +10,318 @@ struct Puzzle {
 }
 
 impl Puzzle {
-    fn load(file_path: &str) -> Self {
-        let text = fs::read_to_string(file_path).unwrap();
-
+    /// Inclusive ranges of fresh IDs, one per line, then a blank line and
+    /// the available IDs, one per line.
+    fn load(text: &str) -> Result<Self> {
         let mut valid_ranges = Vec::new();
         let mut ids = Vec::new();
+        let mut lines = text
+            .lines()
+            .enumerate()
+            .map(|(i, line)| (i + 1, line.trim()));
 
-        for line in text.lines() {
-            if line.is_empty() {
-                continue;
+        let parse_id = |line: usize, id: &str| {
+            let id = id.trim();
+            id.parse::<u64>()
+                .map_err(|_| Error::at(line, format!("invalid ingredient ID '{id}'")))
+        };
+
+        for (line, text) in lines.by_ref() {
+            if text.is_empty() {
+                break;
+            }
+            let (low, high) = text.split_once('-').ok_or_else(|| {
+                Error::at(
+                    line,
+                    format!("expected a range, not '{text}' (IDs follow a blank line)"),
+                )
+            })?;
+            let (low, high) = (parse_id(line, low)?, parse_id(line, high)?);
+            if low > high {
+                return Err(Error::at(
+                    line,
+                    format!("range '{text}' ends before it starts"),
+                ));
             }
+            valid_ranges.push((low, high));
+        }
 
-            if let Some((left, right)) = line.split_once("-") {
-                let left: u64 = left.parse().unwrap();
-                let right: u64 = right.parse().unwrap();
-                valid_ranges.push((left, right));
-            } else {
-                let line: u64 = line.parse().unwrap();
-                ids.push(line);
+        for (line, text) in lines {
+            if text.is_empty() {
+                continue;
+            }
+            if text.contains('-') {
+                return Err(Error::at(
+                    line,
+                    format!("expected an ingredient ID, not the range '{text}'"),
+                ));
             }
+            ids.push(parse_id(line, text)?);
+        }
+
+        Ok(Self { valid_ranges, ids })
+    }
+}
+
+impl Puzzle {
+    fn fresh(&self) -> IntervalSet<u64> {
+        self.valid_ranges.iter().copied().collect()
+    }
+}
+
+/// Answers, one ID at a time, whether it is fresh and which ranges say so.
+struct Freshness<'a> {
+    ranges: &'a [(u64, u64)],
+    fresh: IntervalSet<u64>,
+    /// Indices into `ranges`, ordered by where each range starts. Read as an
+    /// implicit search tree: the middle of any slice is the root of it.
+    by_start: Vec<usize>,
+    /// The furthest end in the subtree rooted at each position of `by_start`.
+    max_high: Vec<u64>,
+}
+
+impl<'a> Freshness<'a> {
+    fn new(ranges: &'a [(u64, u64)]) -> Self {
+        let mut by_start: Vec<usize> = (0..ranges.len()).collect();
+        by_start.sort_by_key(|&i| ranges[i]);
+
+        let mut index = Self {
+            ranges,
+            fresh: ranges.iter().copied().collect(),
+            max_high: vec![0; by_start.len()],
+            by_start,
+        };
+        index.build(0, ranges.len());
+        index
+    }
+
+    /// Fills in `max_high` for the subtree over `lo..hi`, returning its max.
+    fn build(&mut self, lo: usize, hi: usize) -> u64 {
+        if lo >= hi {
+            return 0;
+        }
+        let mid = lo + (hi - lo) / 2;
+        let high = self.ranges[self.by_start[mid]].1;
+        let high = high.max(self.build(lo, mid)).max(self.build(mid + 1, hi));
+        self.max_high[mid] = high;
+        high
+    }
+
+    fn is_fresh(&self, id: u64) -> bool {
+        self.fresh.contains(id)
+    }
+
+    /// Indices of the ranges containing `id`, in the order they were given.
+    fn containing(&self, id: u64) -> Vec<usize> {
+        let mut found = Vec::new();
+        self.search(id, 0, self.by_start.len(), &mut found);
+        found.sort();
+        found
+    }
+
+    fn search(&self, id: u64, lo: usize, hi: usize, found: &mut Vec<usize>) {
+        if lo >= hi {
+            return;
+        }
+        let mid = lo + (hi - lo) / 2;
+        // nothing in this subtree reaches as far as id
+        if self.max_high[mid] < id {
+            return;
         }
 
-        Self { valid_ranges, ids }
+        self.search(id, lo, mid, found);
+        let (low, high) = self.ranges[self.by_start[mid]];
+        // everything from here on starts after id
+        if low > id {
+            return;
+        }
+        if id <= high {
+            found.push(self.by_start[mid]);
+        }
+        self.search(id, mid + 1, hi, found);
     }
 }
 
 fn part1(puzzle: &Puzzle) -> usize {
-    let mut output = 0;
-    let mut ids = puzzle.ids.clone();
-    ids.sort();
+    let index = Freshness::new(&puzzle.valid_ranges);
+    puzzle.ids.iter().filter(|&&id| index.is_fresh(id)).count()
+}
 
-    for &(start_id, stop_id) in puzzle.valid_ranges.iter() {
-        let start_index = ids.partition_point(|&x| x < start_id);
-        let stop_index = ids.partition_point(|&x| x <= stop_id);
+fn part2(puzzle: &Puzzle) -> u128 {
+    puzzle.fresh().len()
+}
+
+fn show_ranges(ranges: impl Iterator<Item = (u64, u64)>) -> String {
+    let ranges: Vec<String> = ranges.map(|(low, high)| format!("{low}-{high}")).collect();
+    if ranges.is_empty() {
+        "none".to_string()
+    } else {
+        ranges.join(", ")
+    }
+}
 
-        output += stop_index - start_index;
-        ids.drain(start_index..stop_index);
+/// Ranges the others already cover between them, in the order given. One
+/// sweep over where the ranges start and stop finds every stretch that a
+/// single range covers alone, and that range is the one it needs.
+fn redundant(ranges: &[(u64, u64)]) -> impl Iterator<Item = (u64, u64)> + '_ {
+    // ranges stop one past their end, widened so u64::MAX can stop too
+    let mut events: Vec<(u128, bool, usize)> = ranges
+        .iter()
+        .enumerate()
+        .flat_map(|(i, &(low, high))| {
+            [(u128::from(low), true, i), (u128::from(high) + 1, false, i)]
+        })
+        .collect();
+    events.sort_unstable();
 
-        if ids.is_empty() {
-            break;
+    let mut needed = vec![false; ranges.len()];
+    // with one range covering, the sum of the covering indices is its index
+    let (mut covering, mut index_sum) = (0, 0);
+    for (k, &(at, starts, i)) in events.iter().enumerate() {
+        if starts {
+            covering += 1;
+            index_sum += i;
+        } else {
+            covering -= 1;
+            index_sum -= i;
+        }
+        let last_here = events.get(k + 1).is_none_or(|&(next, _, _)| next > at);
+        if last_here && covering == 1 {
+            needed[index_sum] = true;
         }
     }
 
-    output
+    ranges
+        .iter()
+        .zip(needed)
+        .filter(|&(_, needed)| !needed)
+        .map(|(&range, _)| range)
 }
 
-fn part2(puzzle: &Puzzle) -> u64 {
-    let mut ranges: Vec<(u64, i32)> = Vec::new();
+/// The fresh ranges once merged, the gaps between them, ranges that other
+/// ranges already cover, and how the listed IDs fall.
+fn show_coverage(text: &str, _params: &Params) -> Result<()> {
+    let puzzle = Puzzle::load(text)?;
+    let fresh = puzzle.fresh();
 
-    for &(start_id, stop_id) in puzzle.valid_ranges.iter() {
-        ranges.push((start_id, 1));
-        ranges.push((stop_id + 1, -1)); // convert to exclusive range
+    println!("fresh: {} ({} IDs)", show_ranges(fresh.iter()), fresh.len());
+    if !fresh.is_empty() {
+        let (low, _) = fresh.iter().next().unwrap();
+        let (_, high) = fresh.iter().last().unwrap();
+        println!("gaps: {}", show_ranges(fresh.complement(low, high).iter()));
     }
 
-    ranges.sort_by_key(|&(id, _)| id);
+    println!(
+        "covered by other ranges: {}",
+        show_ranges(redundant(&puzzle.valid_ranges))
+    );
 
-    let mut last_id = 0;
-    let mut nesting_counter = 0;
-    let mut output = 0;
+    let ids: IntervalSet<u64> = puzzle.ids.iter().map(|&id| (id, id)).collect();
+    println!(
+        "distinct IDs: {} fresh, {} spoiled",
+        ids.intersection(&fresh).len(),
+        ids.difference(&fresh).len()
+    );
 
-    for &(id, t) in ranges.iter() {
-        if nesting_counter == 0 {
-            last_id = id;
-        }
+    Ok(())
+}
 
-        nesting_counter += t;
+/// Every ID, whether it is fresh and the ranges that contain it.
+fn show_ids(text: &str, _params: &Params) -> Result<()> {
+    let puzzle = Puzzle::load(text)?;
+    let index = Freshness::new(&puzzle.valid_ranges);
 
-        if nesting_counter == 0 {
-            output += id - last_id;
+    for &id in &puzzle.ids {
+        let containing = index.containing(id);
+        if index.is_fresh(id) {
+            let ranges = containing.iter().map(|&i| puzzle.valid_ranges[i]);
+            println!("{id}: fresh, in {}", show_ranges(ranges));
+        } else {
+            println!("{id}: spoiled");
         }
     }
 
-    output
+    Ok(())
+}
+
+pub const DAY: Day = Day {
+    number: 5,
+    params: &[],
+    parse: |text| Puzzle::load(text).map(drop),
+    run,
+    visualize: None,
+    views: &[
+        View {
+            name: "coverage",
+            help: "merged fresh ranges, the gaps between them and redundant ranges",
+            show: show_coverage,
+        },
+        View {
+            name: "ids",
+            help: "each ID with whether it is fresh and the ranges containing it",
+            show: show_ids,
+        },
+    ],
+};
+
+fn run(text: &str, _params: &Params, solution: &mut Solution) -> Result<()> {
+    let puzzle = solution.time("parse", || Puzzle::load(text))?;
+
+    solution.part("part1", || part1(&puzzle));
+    solution.part("part2", || part2(&puzzle));
+
+    Ok(())
 }
 
-pub fn run() {
-    println!("\n--- Day 5 ---");
-    let puzzle = Puzzle::load("input/day5.txt");
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fuzz::Rng;
 
-    let now1 = std::time::Instant::now();
-    let result1 = part1(&puzzle);
-    let elapsed1 = now1.elapsed();
-    println!("part1: {}", result1);
-    println!("part1 took: {:.2?}", elapsed1);
+    /// Indices of the ranges containing `id`, by looking at every one.
+    fn scan(ranges: &[(u64, u64)], id: u64) -> Vec<usize> {
+        (0..ranges.len())
+            .filter(|&i| ranges[i].0 <= id && id <= ranges[i].1)
+            .collect()
+    }
+
+    #[test]
+    fn index_matches_a_scan() {
+        let mut rng = Rng(0x853c_49e6_748f_ea9b);
+        let mut next = move |bound: u64| rng.next() % bound;
+
+        for _ in 0..2000 {
+            let ranges: Vec<(u64, u64)> = (0..next(12))
+                .map(|_| {
+                    let (low, length) = (next(60), next(20));
+                    // some pushed against each end of the domain
+                    match next(4) {
+                        0 => (0, length),
+                        1 => (u64::MAX - length, u64::MAX),
+                        _ => (low, low + length),
+                    }
+                })
+                .collect();
+            let index = Freshness::new(&ranges);
 
-    let now2 = std::time::Instant::now();
-    println!("part2: {}", part2(&puzzle));
-    let elapsed2 = now2.elapsed();
-    println!("part2 took: {:.2?}", elapsed2);
+            let probes = ranges.iter().flat_map(|&(low, high)| {
+                [
+                    low.checked_sub(1),
+                    Some(low),
+                    Some(high),
+                    high.checked_add(1),
+                ]
+            });
+            let extremes = [0, 1, u64::MAX - 1, u64::MAX];
+            for id in probes.flatten().chain(extremes).chain(0..90) {
+                let scanned = scan(&ranges, id);
+                assert_eq!(index.containing(id), scanned, "{id} in {ranges:?}");
+                assert_eq!(
+                    index.is_fresh(id),
+                    !scanned.is_empty(),
+                    "{id} in {ranges:?}"
+                );
+            }
+        }
+    }
 }
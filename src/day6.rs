@@ -1,8 +1,11 @@
-use std::fs;
 use std::iter::zip;
 
 use itertools::{Itertools, iproduct};
 
+use crate::error::{Error, Result};
+use crate::params::Params;
+use crate::runner::{Day, Solution};
+
 #[derive(Debug, Clone, Copy)]
 enum Op {
     Add,
@@ -28,37 +31,62 @@ struct Puzzle {
 }
 
 impl Puzzle {
-    fn load(path: &str) -> Self {
-        let input = fs::read_to_string(path).unwrap();
-
+    fn load(input: &str) -> Result<Self> {
         let lines: Vec<&str> = input.lines().collect();
-        let nums: Vec<i64> = lines[..lines.len() - 1]
-            .iter()
-            .flat_map(|&line| line.split_whitespace())
-            .map(|num| num.parse().unwrap())
-            .collect();
-
-        let height = lines.len() - 1;
-        let width = nums.len() / height;
+        let Some((op_line, num_lines)) = lines.split_last().filter(|(_, rows)| !rows.is_empty())
+        else {
+            return Err(Error::new(
+                "expected rows of numbers followed by a row of operators",
+            ));
+        };
+
+        let height = num_lines.len();
+        let width = num_lines[0].split_whitespace().count();
+        let mut nums: Vec<i64> = Vec::new();
+        for (i, line) in num_lines.iter().enumerate() {
+            let row: Vec<i64> = line
+                .split_whitespace()
+                .map(|num| {
+                    num.parse()
+                        .map_err(|_| Error::at(i + 1, format!("invalid number '{num}'")))
+                })
+                .collect::<Result<_>>()?;
+            if row.len() != width {
+                return Err(Error::at(
+                    i + 1,
+                    format!("expected {width} numbers, found {}", row.len()),
+                ));
+            }
+            nums.extend(row);
+        }
 
-        let ops: Vec<Op> = lines
-            .last()
-            .unwrap()
+        let ops: Vec<Op> = op_line
             .split_whitespace()
             .map(|s| match s {
-                "*" => Op::Mul,
-                "+" => Op::Add,
-                _ => Op::Add,
+                "*" => Ok(Op::Mul),
+                "+" => Ok(Op::Add),
+                _ => Err(Error::at(lines.len(), format!("unknown operator '{s}'"))),
             })
-            .collect();
+            .collect::<Result<_>>()?;
+        if ops.len() != width {
+            return Err(Error::at(
+                lines.len(),
+                format!("expected {width} operators, found {}", ops.len()),
+            ));
+        }
 
-        // parsing for part2
-        let chars: Vec<char> = lines[..lines.len() - 1]
+        // parsing for part2, padding rows that lost their trailing spaces
+        let line_width = num_lines
+            .iter()
+            .map(|line| line.chars().count())
+            .max()
+            .unwrap_or(0);
+        let chars: Vec<char> = num_lines
             .iter()
-            .flat_map(|&line| line.chars())
+            .flat_map(|&line| line.chars().pad_using(line_width, |_| ' '))
             .collect();
 
-        let rotated_chars: Vec<char> = rotate_counter_clockwise(&chars, lines[0].len(), height);
+        let rotated_chars: Vec<char> = rotate_counter_clockwise(&chars, line_width, height);
         let rotated_lines: Vec<String> = rotated_chars
             .chunks(height)
             .map(|line| line.iter().collect())
@@ -70,20 +98,20 @@ impl Puzzle {
             .map(|line| line.trim().parse::<i64>().ok())
             .chunk_by(|value| value.is_some())
         {
-            let line_nums: Vec<i64> = chunk.filter_map(|value| value).collect();
+            let line_nums: Vec<i64> = chunk.flatten().collect();
             if !line_nums.is_empty() {
                 rotated_nums.push(line_nums);
             }
         }
         rotated_nums.reverse(); // So that we align with ops
 
-        Self {
+        Ok(Self {
             nums,
             rotated_nums,
             ops,
             width,
             height,
-        }
+        })
     }
 }
 
@@ -120,11 +148,20 @@ fn part2(puzzle: &Puzzle) -> i64 {
         .sum()
 }
 
-pub fn run() {
-    println!("\n--- Day 6 ---");
+pub const DAY: Day = Day {
+    number: 6,
+    params: &[],
+    parse: |text| Puzzle::load(text).map(drop),
+    run,
+    visualize: None,
+    views: &[],
+};
+
+fn run(text: &str, _params: &Params, solution: &mut Solution) -> Result<()> {
+    let input = solution.time("parse", || Puzzle::load(text))?;
 
-    let input = Puzzle::load("input/day6.txt");
+    solution.part("part1", || part1(&input));
+    solution.part("part2", || part2(&input));
 
-    println!("part1: {}", part1(&input));
-    println!("part2: {}", part2(&input));
+    Ok(())
 }
@@ -1,4 +1,9 @@
-use std::{collections::VecDeque, fs};
+use std::collections::VecDeque;
+
+use crate::error::{Error, Result};
+use crate::params::Params;
+use crate::runner::{Day, Solution};
+use crate::svg;
 
 #[derive(Clone, Copy, Debug, PartialEq)]
 enum Tile {
@@ -17,9 +22,7 @@ struct Puzzle {
 }
 
 impl Puzzle {
-    fn load(path: &str) -> Puzzle {
-        let input = fs::read_to_string(path).unwrap();
-
+    fn load(input: &str) -> Result<Puzzle> {
         let map: Vec<Tile> = input
             .chars()
             .flat_map(|c| match c {
@@ -30,17 +33,27 @@ impl Puzzle {
             })
             .collect();
 
-        let width = input.chars().position(|c| c == '\n').unwrap();
+        let width = input
+            .chars()
+            .take_while(|&c| c != '\n')
+            .filter(|c| matches!(c, '.' | '^' | 'S'))
+            .count();
+        if width == 0 || !map.len().is_multiple_of(width) {
+            return Err(Error::new("the manifold must be a non-empty rectangle"));
+        }
+        if !map.contains(&Tile::Start) {
+            return Err(Error::new("the manifold has no start 'S'"));
+        }
         let height = map.len() / width;
 
         let possibilities: Vec<i64> = map.iter().map(|_| 0).collect();
 
-        Puzzle {
+        Ok(Puzzle {
             map,
             possibilities,
             width: width as i64,
             height: height as i64,
-        }
+        })
     }
 
     fn in_bounds(&self, x: i64, y: i64) -> bool {
@@ -102,8 +115,44 @@ fn solve(puzzle: &mut Puzzle) -> (i64, i64) {
     (part1, part2)
 }
 
-pub fn run() {
-    let mut puzzle = Puzzle::load("input/day7.txt");
-    let (part1, part2) = solve(&mut puzzle);
-    println!("part1: {}\npart2: {}", part1, part2);
+/// The manifold after the beam has run, showing splitters and beam paths.
+fn visualize(text: &str, _params: &Params) -> Result<String> {
+    let mut puzzle = Puzzle::load(text)?;
+    solve(&mut puzzle);
+
+    let cells = |tile: Tile| -> Vec<(i64, i64)> {
+        (0..puzzle.map.len() as i64)
+            .filter(|&i| puzzle.map[i as usize] == tile)
+            .map(|i| (i % puzzle.width, i / puzzle.width))
+            .collect()
+    };
+
+    Ok(svg::grid(
+        puzzle.width,
+        puzzle.height,
+        &[
+            ("#f2c14e", cells(Tile::Beam)),
+            ("#333", cells(Tile::Splitter)),
+            ("#c0392b", cells(Tile::Start)),
+        ],
+    ))
+}
+
+pub const DAY: Day = Day {
+    number: 7,
+    params: &[],
+    parse: |text| Puzzle::load(text).map(drop),
+    run,
+    visualize: Some(visualize),
+    views: &[],
+};
+
+fn run(text: &str, _params: &Params, solution: &mut Solution) -> Result<()> {
+    let mut puzzle = solution.time("parse", || Puzzle::load(text))?;
+
+    let (part1, part2) = solution.time("solve", || solve(&mut puzzle));
+    solution.answer("part1", part1);
+    solution.answer("part2", part2);
+
+    Ok(())
 }
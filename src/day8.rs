@@ -1,4 +1,8 @@
-use std::{fs, ops::Sub, time};
+use std::ops::Sub;
+
+use crate::error::{Error, Result};
+use crate::params::{Param, Params};
+use crate::runner::{Day, Solution};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 struct Point {
@@ -38,27 +42,39 @@ struct Puzzle {
 }
 
 impl Puzzle {
-    fn load(path: &str) -> Puzzle {
-        let inputs = fs::read_to_string(path).unwrap();
-
+    fn load(inputs: &str) -> Result<Puzzle> {
         let points: Vec<Point> = inputs
             .lines()
-            .map(|line| {
-                let mut comps = line.split(",").map(|x| x.parse::<i64>().unwrap());
-
-                Point {
-                    x: comps.next().unwrap(),
-                    y: comps.next().unwrap(),
-                    z: comps.next().unwrap(),
+            .enumerate()
+            .map(|(i, line)| {
+                let comps: Vec<i64> = line
+                    .split(",")
+                    .map(|x| x.parse::<i64>())
+                    .collect::<std::result::Result<_, _>>()
+                    .map_err(|_| Error::at(i + 1, format!("invalid position '{line}'")))?;
+
+                match comps.as_slice() {
+                    &[x, y, z] => Ok(Point { x, y, z }),
+                    _ => Err(Error::at(i + 1, "expected three coordinates")),
                 }
             })
-            .collect();
+            .collect::<Result<_>>()?;
+
+        if points.is_empty() {
+            return Err(Error::new("no junction boxes"));
+        }
 
-        Puzzle { points }
+        Ok(Puzzle { points })
     }
 }
 
-fn solve(puzzle: &Puzzle, part1_iterations: usize) {
+fn solve(
+    puzzle: &Puzzle,
+    part1_iterations: usize,
+    part1_groups: usize,
+) -> (Option<usize>, Option<i64>) {
+    let mut part1 = None;
+
     let mut connections: Vec<Connection> = puzzle.points[..puzzle.points.len() - 1]
         .iter()
         .enumerate()
@@ -93,21 +109,48 @@ fn solve(puzzle: &Puzzle, part1_iterations: usize) {
             group_sizes.sort();
             group_sizes.reverse();
 
-            let part1 = group_sizes[0] * group_sizes[1] * group_sizes[2];
-            println!("Part 1: {}", part1);
+            part1 = Some(group_sizes.iter().take(part1_groups).product());
         }
         if groups.len() == 1 {
-            println!("Part 2: {}", conn.from.x * conn.to.x);
-            break;
+            return (part1, Some(conn.from.x * conn.to.x));
         }
     }
+
+    (part1, None)
 }
 
-pub fn run() {
-    let start = time::Instant::now();
-    let puzzle = Puzzle::load("input/day8.txt");
-    solve(&puzzle, 1000);
+pub const DAY: Day = Day {
+    number: 8,
+    params: &[
+        Param::new(
+            "connections",
+            "1000",
+            "connections made before part 1 is scored",
+        ),
+        Param::new(
+            "groups",
+            "3",
+            "number of largest circuits multiplied in part 1",
+        ),
+    ],
+    parse: |text| Puzzle::load(text).map(drop),
+    run,
+    visualize: None,
+    views: &[],
+};
+
+fn run(text: &str, params: &Params, solution: &mut Solution) -> Result<()> {
+    let connections: usize = params.get("connections")?;
+    let groups: usize = params.get("groups")?;
+    let puzzle = solution.time("parse", || Puzzle::load(text))?;
+
+    let (part1, part2) = solution.time("solve", || solve(&puzzle, connections, groups));
+    if let Some(part1) = part1 {
+        solution.answer("part1", part1);
+    }
+    if let Some(part2) = part2 {
+        solution.answer("part2", part2);
+    }
 
-    let delta = start.elapsed();
-    println!("Time: {:.2?}", delta);
+    Ok(())
 }
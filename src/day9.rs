@@ -1,4 +1,6 @@
-use std::{fs, time};
+use crate::error::{Error, Result};
+use crate::params::Params;
+use crate::runner::{Day, Solution};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 struct Point {
@@ -49,7 +51,7 @@ impl Line {
         let h_max_x = std::cmp::max(h_seg.a.x, h_seg.b.x);
         let h_y = h_seg.a.y;
 
-        return h_min_x < v_x && v_x < h_max_x && v_min_y < h_y && h_y < v_max_y;
+        h_min_x < v_x && v_x < h_max_x && v_min_y < h_y && h_y < v_max_y
     }
 }
 
@@ -105,20 +107,24 @@ struct Puzzle {
 }
 
 impl Puzzle {
-    fn load(path: &str) -> Self {
-        let text = fs::read_to_string(path).unwrap();
-
+    fn load(text: &str) -> Result<Self> {
         let points: Vec<Point> = text
             .lines()
-            .map(|line| {
-                let (x, y) = line.split_once(",").unwrap();
-
-                Point {
-                    x: x.parse().unwrap(),
-                    y: y.parse().unwrap(),
-                }
+            .enumerate()
+            .map(|(i, line)| {
+                let invalid = || Error::at(i + 1, format!("invalid tile '{line}'"));
+                let (x, y) = line.split_once(",").ok_or_else(invalid)?;
+
+                Ok(Point {
+                    x: x.parse().map_err(|_| invalid())?,
+                    y: y.parse().map_err(|_| invalid())?,
+                })
             })
-            .collect();
+            .collect::<Result<_>>()?;
+
+        if points.len() < 2 {
+            return Err(Error::new("expected at least two red tiles"));
+        }
 
         let mut polygon: Vec<Line> = points[..points.len() - 1]
             .iter()
@@ -127,7 +133,7 @@ impl Puzzle {
             .collect();
         polygon.push(Line::new(*points.last().unwrap(), *points.first().unwrap()));
 
-        return Puzzle { points, polygon };
+        Ok(Puzzle { points, polygon })
     }
 
     fn point_in_polygon(&self, point: &Point) -> bool {
@@ -168,8 +174,8 @@ impl Puzzle {
     }
 }
 
-fn part1(puzzle: &Puzzle) {
-    let dist = puzzle.points[..puzzle.points.len() - 1]
+fn part1(puzzle: &Puzzle) -> i64 {
+    puzzle.points[..puzzle.points.len() - 1]
         .iter()
         .enumerate()
         .flat_map(|(i, a)| {
@@ -178,32 +184,74 @@ fn part1(puzzle: &Puzzle) {
                 .map(|b| Rect::new(*a, *b).area())
         })
         .max()
-        .unwrap();
-
-    println!("Part 1: {}", dist);
+        .unwrap()
 }
 
-fn part2(puzzle: &Puzzle) {
-    let dist = puzzle.points[..puzzle.points.len() - 1]
+fn largest_inner_rect(puzzle: &Puzzle) -> Result<Rect> {
+    puzzle.points[..puzzle.points.len() - 1]
         .iter()
         .enumerate()
         .flat_map(|(i, a)| puzzle.points[i + 1..].iter().map(|b| Rect::new(*a, *b)))
         .filter(|rect| puzzle.rect_in_polygon(rect))
-        .map(|rect| rect.area())
-        .max()
-        .unwrap();
+        .max_by_key(|rect| rect.area())
+        .ok_or_else(|| Error::new("no rectangle between red tiles fits inside the loop"))
+}
+
+fn part2(puzzle: &Puzzle) -> Result<i64> {
+    Ok(largest_inner_rect(puzzle)?.area())
+}
 
-    println!("Part 2: {}", dist);
+/// The tile polygon with the part 2 rectangle drawn inside it.
+fn visualize(text: &str, _params: &Params) -> Result<String> {
+    let puzzle = Puzzle::load(text)?;
+    let rect = largest_inner_rect(&puzzle)?;
+
+    let min_x = puzzle.points.iter().map(|p| p.x).min().unwrap();
+    let max_x = puzzle.points.iter().map(|p| p.x).max().unwrap();
+    let min_y = puzzle.points.iter().map(|p| p.y).min().unwrap();
+    let max_y = puzzle.points.iter().map(|p| p.y).max().unwrap();
+    let margin = std::cmp::max(max_x - min_x, max_y - min_y) / 50 + 1;
+
+    let points: Vec<String> = puzzle
+        .points
+        .iter()
+        .map(|p| format!("{},{}", p.x, p.y))
+        .collect();
+    let [corner, _, far, _] = rect.corners;
+
+    Ok(format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"{} {} {} {}\">\
+         <polygon points=\"{}\" fill=\"#cfe3d4\" stroke=\"#2a8a3e\" \
+         stroke-width=\"2\" vector-effect=\"non-scaling-stroke\"/>\
+         <rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"#e0904a\" \
+         fill-opacity=\"0.6\" stroke=\"#c0392b\" vector-effect=\"non-scaling-stroke\"/></svg>",
+        min_x - margin,
+        min_y - margin,
+        max_x - min_x + 2 * margin,
+        max_y - min_y + 2 * margin,
+        points.join(" "),
+        corner.x,
+        corner.y,
+        far.x - corner.x,
+        far.y - corner.y,
+    ))
 }
 
-pub fn run() {
-    let puzzle = Puzzle::load("input/day9.txt");
+pub const DAY: Day = Day {
+    number: 9,
+    params: &[],
+    parse: |text| Puzzle::load(text).map(drop),
+    run,
+    visualize: Some(visualize),
+    views: &[],
+};
 
-    part1(&puzzle);
+fn run(text: &str, _params: &Params, solution: &mut Solution) -> Result<()> {
+    let puzzle = solution.time("parse", || Puzzle::load(text))?;
 
-    let now = time::Instant::now();
-    part2(&puzzle);
-    let duration = now.elapsed();
+    solution.part("part1", || part1(&puzzle));
+    let part2 = solution.time("part2", || part2(&puzzle))?;
+    solution.answer("part2", part2);
 
-    println!("Part 2 time: {:.2?}", duration);
+    Ok(())
 }
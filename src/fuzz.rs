@@ -0,0 +1,227 @@
+use std::{
+    collections::HashSet,
+    fs,
+    hash::{DefaultHasher, Hash, Hasher},
+    panic::{self, AssertUnwindSafe},
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::config::Config;
+use crate::runner::Day;
+
+const FUZZ_DIR: &str = "fuzz";
+
+/// Bytes the parsers care about: separators, signs, digits and grid symbols,
+/// plus a couple that break UTF-8.
+const INTERESTING: &[u8] = b"\n\r \t,:-+x#@.^S*[](){}0189\xff\xc3";
+
+const NUMBERS: &[&str] = &[
+    "0",
+    "-1",
+    "65536",
+    "2147483648",
+    "-2147483648",
+    "18446744073709551616",
+    "99999999999999999999999999999999999999999",
+];
+
+/// xorshift64*, plenty for picking mutations and for the random cases in
+/// tests. The seed must not be zero.
+pub(crate) struct Rng(pub(crate) u64);
+
+impl Rng {
+    pub(crate) fn next(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+
+    pub(crate) fn below(&mut self, n: usize) -> usize {
+        (self.next() % n.max(1) as u64) as usize
+    }
+
+    pub(crate) fn pick<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.below(items.len())]
+    }
+}
+
+fn mutate(rng: &mut Rng, input: &[u8], corpus: &[Vec<u8>]) -> Vec<u8> {
+    let mut data = input.to_vec();
+
+    for _ in 0..=rng.below(4) {
+        let pos = rng.below(data.len() + 1);
+        let end = (pos + 1 + rng.below(32)).min(data.len());
+
+        match rng.below(8) {
+            0 if pos < data.len() => data[pos] ^= 1 << rng.below(8),
+            1 if pos < data.len() => data[pos] = *rng.pick(INTERESTING),
+            2 => data.insert(pos, *rng.pick(INTERESTING)),
+            3 if pos < end => {
+                data.drain(pos..end);
+            }
+            4 if pos < end => {
+                let chunk = data[pos..end].to_vec();
+                let at = rng.below(data.len() + 1);
+                data.splice(at..at, chunk);
+            }
+            5 => data.truncate(pos),
+            6 => {
+                let other = rng.pick(corpus);
+                let from = rng.below(other.len() + 1);
+                data.truncate(pos);
+                data.extend_from_slice(&other[from..]);
+            }
+            _ => {
+                let number = rng.pick(NUMBERS).as_bytes();
+                data.splice(pos..pos, number.iter().copied());
+            }
+        }
+    }
+
+    data
+}
+
+fn file_name(data: &[u8]) -> String {
+    let mut hasher = DefaultHasher::new();
+    data.hash(&mut hasher);
+    format!("{:016x}.txt", hasher.finish())
+}
+
+fn read_dir(dir: &Path) -> Vec<Vec<u8>> {
+    let mut paths: Vec<PathBuf> = fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|entry| entry.path())
+        .collect();
+    paths.sort();
+    paths
+        .iter()
+        .filter_map(|path| fs::read(path).ok())
+        .collect()
+}
+
+/// Seeds a day's corpus from its example inputs (and real input, if there is
+/// one) the first time it is fuzzed.
+fn seed_corpus(day: &Day, config: &Config, dir: &Path) -> Vec<Vec<u8>> {
+    let corpus = read_dir(dir);
+    if !corpus.is_empty() {
+        return corpus;
+    }
+
+    let prefix = format!("day{}_example", day.number);
+    let mut seeds: Vec<Vec<u8>> = fs::read_dir(&config.input_dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter(|entry| entry.file_name().to_string_lossy().starts_with(&prefix))
+        .filter_map(|entry| fs::read(entry.path()).ok())
+        .collect();
+    seeds.extend(fs::read(config.input_path(day.number)).ok());
+    seeds.push(Vec::new());
+
+    let _ = fs::create_dir_all(dir);
+    for seed in seeds.iter() {
+        let _ = fs::write(dir.join(file_name(seed)), seed);
+    }
+    seeds
+}
+
+/// Coarse bucket for a parse result, so that the corpus only grows when an
+/// input reaches a new kind of error rather than every new number.
+fn outcome(result: &crate::error::Result<()>) -> String {
+    match result {
+        Ok(()) => "ok".to_string(),
+        Err(err) => err
+            .message
+            .split('\'')
+            .next()
+            .unwrap_or_default()
+            .chars()
+            .filter(|c| !c.is_ascii_digit())
+            .collect(),
+    }
+}
+
+fn panic_message(payload: &(dyn std::any::Any + Send)) -> String {
+    if let Some(s) = payload.downcast_ref::<&str>() {
+        s.to_string()
+    } else if let Some(s) = payload.downcast_ref::<String>() {
+        s.clone()
+    } else {
+        "unknown panic".to_string()
+    }
+}
+
+/// Feeds mutated inputs to the day's parser, which must return either a value
+/// or an error. Inputs that panic are saved under `fuzz/crashes/day<N>`.
+/// Returns the number of crashing inputs found.
+pub fn fuzz(day: &Day, config: &Config, iterations: usize) -> usize {
+    let corpus_dir = Path::new(FUZZ_DIR)
+        .join("corpus")
+        .join(format!("day{}", day.number));
+    let crash_dir = Path::new(FUZZ_DIR)
+        .join("crashes")
+        .join(format!("day{}", day.number));
+
+    let mut corpus = seed_corpus(day, config, &corpus_dir);
+    // the corpus and earlier crashes are replayed first so fixes can be confirmed
+    let mut replay = corpus.clone();
+    replay.extend(read_dir(&crash_dir));
+
+    let seed = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(1, |d| d.as_nanos() as u64)
+        | 1;
+    let mut rng = Rng(seed);
+    let mut seen: HashSet<String> = HashSet::new();
+    let mut crashes: HashSet<Vec<u8>> = HashSet::new();
+
+    let hook = panic::take_hook();
+    panic::set_hook(Box::new(|_| {}));
+
+    let inputs = replay.len() + iterations;
+    for i in 0..inputs {
+        let data = if i < replay.len() {
+            replay[i].clone()
+        } else {
+            let base = rng.pick(&corpus).clone();
+            mutate(&mut rng, &base, &corpus)
+        };
+        let text = String::from_utf8_lossy(&data);
+
+        match panic::catch_unwind(AssertUnwindSafe(|| (day.parse)(&text))) {
+            Ok(result) => {
+                if seen.insert(outcome(&result)) && i >= replay.len() {
+                    let _ = fs::write(corpus_dir.join(file_name(&data)), &data);
+                    corpus.push(data);
+                }
+            }
+            Err(payload) => {
+                if crashes.insert(data.clone()) {
+                    let _ = fs::create_dir_all(&crash_dir);
+                    let path = crash_dir.join(file_name(&data));
+                    let _ = fs::write(&path, &data);
+                    println!(
+                        "day{}: panic '{}' on {}",
+                        day.number,
+                        panic_message(payload.as_ref()),
+                        path.display()
+                    );
+                }
+            }
+        }
+    }
+
+    panic::set_hook(hook);
+
+    println!(
+        "day{}: {inputs} inputs, {} in corpus, {} crashes (seed {seed})",
+        day.number,
+        corpus.len(),
+        crashes.len()
+    );
+    crashes.len()
+}
@@ -0,0 +1,306 @@
+use std::fmt::Debug;
+
+/// Integers an `IntervalSet` can hold. Ends are inclusive, so stepping past
+/// either end of the domain is checked rather than wrapping.
+pub trait Point: Copy + Ord + Debug {
+    const MIN: Self;
+    const MAX: Self;
+
+    fn checked_next(self) -> Option<Self>;
+    fn checked_prev(self) -> Option<Self>;
+
+    /// Number of points in `low..=high`, saturating at `u128::MAX`.
+    fn count(low: Self, high: Self) -> u128;
+}
+
+macro_rules! impl_point {
+    ($($t:ty),*) => {$(
+        impl Point for $t {
+            const MIN: Self = <$t>::MIN;
+            const MAX: Self = <$t>::MAX;
+
+            fn checked_next(self) -> Option<Self> {
+                self.checked_add(1)
+            }
+
+            fn checked_prev(self) -> Option<Self> {
+                self.checked_sub(1)
+            }
+
+            fn count(low: Self, high: Self) -> u128 {
+                ((high - low) as u128).saturating_add(1)
+            }
+        }
+    )*};
+}
+
+impl_point!(u64, u128);
+
+/// A set of integers stored as inclusive ranges, kept sorted, disjoint and
+/// never touching, so that every point belongs to exactly one range.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IntervalSet<T> {
+    ranges: Vec<(T, T)>,
+}
+
+impl<T: Point> Default for IntervalSet<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Point> IntervalSet<T> {
+    pub fn new() -> Self {
+        Self { ranges: Vec::new() }
+    }
+
+    /// Adds `low..=high`, joining it with every range it overlaps or
+    /// touches. Nothing is added if `low > high`.
+    pub fn insert(&mut self, low: T, high: T) {
+        if low > high {
+            return;
+        }
+
+        // ranges ending at least one short of low stay before it
+        let start = self
+            .ranges
+            .partition_point(|&(_, end)| end.checked_next().is_some_and(|next| next < low));
+        // and ranges starting at least one past high stay after it
+        let stop = self
+            .ranges
+            .partition_point(|&(begin, _)| high.checked_next().is_none_or(|next| begin <= next));
+
+        let joined = if start < stop {
+            (
+                low.min(self.ranges[start].0),
+                high.max(self.ranges[stop - 1].1),
+            )
+        } else {
+            (low, high)
+        };
+        self.ranges.splice(start..stop, [joined]);
+    }
+
+    /// Adds every range of `other` to this set.
+    #[allow(dead_code)] // part of the set API, no day needs it yet
+    pub fn merge(&mut self, other: &Self) {
+        for &(low, high) in &other.ranges {
+            self.insert(low, high);
+        }
+    }
+
+    #[allow(dead_code)]
+    pub fn union(&self, other: &Self) -> Self {
+        let mut union = self.clone();
+        union.merge(other);
+        union
+    }
+
+    pub fn intersection(&self, other: &Self) -> Self {
+        let mut ranges = Vec::new();
+        let (mut a, mut b) = (
+            self.ranges.iter().peekable(),
+            other.ranges.iter().peekable(),
+        );
+
+        while let (Some(&&(a_low, a_high)), Some(&&(b_low, b_high))) = (a.peek(), b.peek()) {
+            let (low, high) = (a_low.max(b_low), a_high.min(b_high));
+            if low <= high {
+                ranges.push((low, high));
+            }
+            // whichever ends first can't meet anything further on
+            if a_high < b_high {
+                a.next();
+            } else {
+                b.next();
+            }
+        }
+
+        Self { ranges }
+    }
+
+    pub fn difference(&self, other: &Self) -> Self {
+        self.intersection(&other.complement(T::MIN, T::MAX))
+    }
+
+    /// The points of `low..=high` that are not in this set.
+    pub fn complement(&self, low: T, high: T) -> Self {
+        let mut ranges = Vec::new();
+        if low > high {
+            return Self { ranges };
+        }
+
+        // the first point not yet accounted for, None once past T::MAX
+        let mut from = Some(low);
+        for &(begin, end) in &self.ranges {
+            let Some(gap) = from else {
+                break;
+            };
+            if end < gap {
+                continue;
+            }
+            if begin > high {
+                break;
+            }
+            if begin > gap {
+                ranges.push((gap, begin.checked_prev().unwrap()));
+            }
+            from = end.checked_next();
+        }
+
+        if let Some(gap) = from
+            && gap <= high
+        {
+            ranges.push((gap, high));
+        }
+
+        Self { ranges }
+    }
+
+    /// Number of points in the set, saturating at `u128::MAX`.
+    pub fn len(&self) -> u128 {
+        self.ranges.iter().fold(0u128, |len, &(low, high)| {
+            len.saturating_add(T::count(low, high))
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    pub fn contains(&self, point: T) -> bool {
+        let i = self.ranges.partition_point(|&(_, end)| end < point);
+        self.ranges.get(i).is_some_and(|&(begin, _)| begin <= point)
+    }
+
+    /// Whether every point of `low..=high` is in the set.
+    #[allow(dead_code)]
+    pub fn contains_range(&self, low: T, high: T) -> bool {
+        if low > high {
+            return true;
+        }
+        let i = self.ranges.partition_point(|&(_, end)| end < low);
+        self.ranges
+            .get(i)
+            .is_some_and(|&(begin, end)| begin <= low && high <= end)
+    }
+
+    /// The ranges in order, as `(low, high)` pairs.
+    pub fn iter(&self) -> impl Iterator<Item = (T, T)> + '_ {
+        self.ranges.iter().copied()
+    }
+}
+
+impl<T: Point> FromIterator<(T, T)> for IntervalSet<T> {
+    fn from_iter<I: IntoIterator<Item = (T, T)>>(iter: I) -> Self {
+        let mut set = Self::new();
+        for (low, high) in iter {
+            set.insert(low, high);
+        }
+        set
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fuzz::Rng;
+
+    fn ranges<T: Point>(set: &IntervalSet<T>) -> Vec<(T, T)> {
+        set.iter().collect()
+    }
+
+    #[test]
+    fn joins_overlapping_and_touching_ranges() {
+        let set: IntervalSet<u64> = [(10, 14), (3, 5), (6, 8), (13, 20), (30, 31)]
+            .into_iter()
+            .collect();
+        assert_eq!(ranges(&set), [(3, 8), (10, 20), (30, 31)]);
+
+        let mut set = set;
+        set.insert(9, 9);
+        assert_eq!(ranges(&set), [(3, 20), (30, 31)]);
+        set.insert(25, 24);
+        assert_eq!(ranges(&set), [(3, 20), (30, 31)]);
+    }
+
+    #[test]
+    fn joins_at_the_ends_of_the_domain() {
+        let mut set = IntervalSet::new();
+        set.insert(0, u64::MAX - 1);
+        set.insert(u64::MAX, u64::MAX);
+        assert_eq!(ranges(&set), [(0, u64::MAX)]);
+
+        let mut set = IntervalSet::new();
+        set.insert(u64::MAX, u64::MAX);
+        set.insert(0, 0);
+        set.insert(1, u64::MAX - 1);
+        assert_eq!(ranges(&set), [(0, u64::MAX)]);
+    }
+
+    #[test]
+    fn complements_within_the_whole_domain() {
+        let empty = IntervalSet::<u64>::new();
+        assert_eq!(ranges(&empty.complement(0, u64::MAX)), [(0, u64::MAX)]);
+
+        let full: IntervalSet<u64> = [(0, u64::MAX)].into_iter().collect();
+        assert!(full.complement(0, u64::MAX).is_empty());
+
+        let ends: IntervalSet<u64> = [(0, 4), (u64::MAX - 4, u64::MAX)].into_iter().collect();
+        assert_eq!(ranges(&ends.complement(0, u64::MAX)), [(5, u64::MAX - 5)]);
+        assert_eq!(ranges(&ends.complement(3, 10)), [(5, 10)]);
+    }
+
+    #[test]
+    fn counts_the_whole_domain() {
+        let full: IntervalSet<u64> = [(0, u64::MAX)].into_iter().collect();
+        assert_eq!(full.len(), 1 << 64);
+
+        let full: IntervalSet<u128> = [(0, u128::MAX)].into_iter().collect();
+        assert_eq!(full.len(), u128::MAX);
+    }
+
+    #[test]
+    fn matches_sets_of_points() {
+        use std::collections::BTreeSet;
+
+        let mut rng = Rng(0x2545_f491_4f6c_dd1d);
+        let mut next = move |bound: u64| rng.next() % bound;
+        let points = |set: &IntervalSet<u64>| -> BTreeSet<u64> {
+            set.iter().flat_map(|(low, high)| low..=high).collect()
+        };
+
+        for _ in 0..2000 {
+            let mut random =
+                || -> IntervalSet<u64> { (0..next(6)).map(|_| (next(40), next(40))).collect() };
+            let (a, b) = (random(), random());
+            let (pa, pb) = (points(&a), points(&b));
+
+            assert!(
+                a.iter()
+                    .zip(a.iter().skip(1))
+                    .all(|((_, end), (begin, _))| end + 1 < begin)
+            );
+            assert_eq!(a.len(), pa.len() as u128);
+            assert_eq!(points(&a.union(&b)), &pa | &pb);
+            assert_eq!(points(&a.intersection(&b)), &pa & &pb);
+            assert_eq!(points(&a.difference(&b)), &pa - &pb);
+
+            // a = (a ∩ b) ∪ (a \ b), and the two halves don't meet
+            let (common, only) = (a.intersection(&b), a.difference(&b));
+            assert_eq!(common.union(&only), a);
+            assert!(common.intersection(&only).is_empty());
+            assert_eq!(a.intersection(&b), b.intersection(&a));
+            assert_eq!(a.difference(&a), IntervalSet::new());
+
+            let (low, high) = (next(45), next(45));
+            let outside: BTreeSet<u64> = (low..=high).filter(|p| !pa.contains(p)).collect();
+            assert_eq!(points(&a.complement(low, high)), outside);
+            assert_eq!(a.contains(low), pa.contains(&low));
+            assert_eq!(
+                a.contains_range(low, high),
+                (low..=high).all(|p| pa.contains(&p))
+            );
+        }
+    }
+}
@@ -1,3 +1,5 @@
+mod alloc;
+mod config;
 mod day1;
 mod day10;
 mod day11;
@@ -10,42 +12,129 @@ mod day6;
 mod day7;
 mod day8;
 mod day9;
+mod error;
+mod fuzz;
+mod interval;
+mod params;
+mod report;
+mod runner;
+mod svg;
+mod watch;
+
+#[global_allocator]
+static GLOBAL: alloc::CountingAlloc = alloc::CountingAlloc;
+
+use std::path::Path;
+
+use config::Config;
+
+fn print_usage() {
+    println!("Usage: cargo run [options] <day>");
+    println!("       cargo run [options] all");
+    println!("       cargo run [options] watch <day>");
+    println!("       cargo run [options] report [file.html]");
+    println!("       cargo run [options] fuzz <day|all> [iterations]");
+    println!("       cargo run [options] <day> <view>");
+    println!("       cargo run params <day>");
+    println!("Example: cargo run 1");
+    println!();
+    println!("Options:");
+    println!("  --config <path>         config file (default: aoc.toml if present)");
+    println!("  --input <dir>           directory holding day<N>.txt inputs");
+    println!("  --format <text|json>    output format");
+    println!("  --param [dayN.]name=v   override a day parameter, see `params <day>`");
+    println!("  --timing, --no-timing   show or hide timings");
+    println!("  --color, --no-color     enable or disable colored output");
+}
+
+fn write_report(config: &Config, path: &str) {
+    match report::write_report(config, Path::new(path)) {
+        Ok(()) => println!("wrote {path}"),
+        Err(err) => eprintln!("{err}"),
+    }
+}
+
+fn fuzz_iterations(args: &[&str]) -> usize {
+    args.first().and_then(|n| n.parse().ok()).unwrap_or(10_000)
+}
+
+/// The days a command runs with their parameters, or None if it doesn't
+/// use parameters at all.
+fn days_run(args: &[&str]) -> Option<Vec<&'static runner::Day>> {
+    match args {
+        ["all"] | ["report"] | ["report", _] => Some(runner::DAYS.iter().collect()),
+        ["fuzz", ..] | ["params", ..] => None,
+        ["watch", day] | [day] | [day, _] => runner::find_day(day).map(|day| vec![day]),
+        _ => None,
+    }
+}
 
 fn main() {
-    let args: Vec<String> = std::env::args().collect();
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let (config, args) = match Config::from_args(&args) {
+        Ok(parsed) => parsed,
+        Err(err) => {
+            eprintln!("{err}");
+            return;
+        }
+    };
+
+    let mut args: Vec<&str> = args.iter().map(String::as_str).collect();
+    if args.is_empty()
+        && let Some(day) = config.default_day.as_deref()
+    {
+        args.push(day);
+    }
 
-    if args.len() != 2 {
-        println!("Usage: cargo run <day>");
-        println!("Example: cargo run 1");
+    if let Some(days) = days_run(&args)
+        && let Err(err) = config.check_overrides(days)
+    {
+        eprintln!("{err}");
         return;
     }
 
-    match args[1].as_str() {
-        "1" => day1::run(),
-        "2" => day2::run(),
-        "3" => day3::run(),
-        "4" => day4::run(),
-        "5" => day5::run(),
-        "6" => day6::run(),
-        "7" => day7::run(),
-        "8" => day8::run(),
-        "9" => day9::run(),
-        "10" => day10::run(),
-        "11" => day11::run(),
-        "12" => day12::run(),
-        "all" => {
-            day1::run();
-            day2::run();
-            day3::run();
-            day4::run();
-            day5::run();
-            day6::run();
-            day7::run();
-            day8::run();
-            day9::run();
-            day10::run();
-            day11::run();
+    match args.as_slice() {
+        ["all"] => {
+            for day in runner::DAYS.iter() {
+                runner::run_day(day, &config);
+            }
+        }
+        ["watch", day] => match runner::find_day(day) {
+            Some(day) => watch::watch(day, &config),
+            None => println!("'{day}' is not a valid day."),
+        },
+        ["report"] => write_report(&config, "report.html"),
+        ["report", path] => write_report(&config, path),
+        ["fuzz", "all", rest @ ..] => {
+            let iterations = fuzz_iterations(rest);
+            let crashes: usize = runner::DAYS
+                .iter()
+                .map(|day| fuzz::fuzz(day, &config, iterations))
+                .sum();
+            if crashes > 0 {
+                std::process::exit(1);
+            }
         }
-        _ => println!("'{}' is not a valid day.", args[1]),
+        ["fuzz", day, rest @ ..] => match runner::find_day(day) {
+            Some(day) => {
+                if fuzz::fuzz(day, &config, fuzz_iterations(rest)) > 0 {
+                    std::process::exit(1);
+                }
+            }
+            None => println!("'{day}' is not a valid day."),
+        },
+        ["params", day] => match runner::find_day(day) {
+            Some(day) => runner::print_params(day),
+            None => println!("'{day}' is not a valid day."),
+        },
+        [day] => match runner::find_day(day) {
+            Some(day) => runner::run_day(day, &config),
+            None => println!("'{day}' is not a valid day."),
+        },
+        [day, view] => match runner::find_day(day) {
+            Some(day) => runner::run_view(day, view, &config),
+            None => println!("'{day}' is not a valid day."),
+        },
+        _ => print_usage(),
     }
 }
@@ -0,0 +1,58 @@
+use std::{collections::HashMap, fmt::Display, str::FromStr};
+
+use crate::error::{Error, Result};
+
+/// A tunable value a day declares, along with its default.
+#[derive(Debug, Clone, Copy)]
+pub struct Param {
+    pub name: &'static str,
+    pub default: &'static str,
+    pub help: &'static str,
+}
+
+impl Param {
+    pub const fn new(name: &'static str, default: &'static str, help: &'static str) -> Self {
+        Self {
+            name,
+            default,
+            help,
+        }
+    }
+}
+
+/// Resolved parameter values for a single day, kept as strings so that
+/// defaults, config file and command line values share the same parsing.
+#[derive(Debug, Clone, Default)]
+pub struct Params {
+    values: HashMap<String, String>,
+}
+
+impl Params {
+    pub fn with_defaults(declared: &[Param]) -> Self {
+        let values = declared
+            .iter()
+            .map(|param| (param.name.to_string(), param.default.to_string()))
+            .collect();
+        Self { values }
+    }
+
+    pub fn set(&mut self, name: &str, value: &str) -> Result<()> {
+        match self.values.get_mut(name) {
+            Some(slot) => {
+                *slot = value.to_string();
+                Ok(())
+            }
+            None => Err(Error::new(format!("unknown parameter '{name}'"))),
+        }
+    }
+
+    pub fn get<T: FromStr<Err: Display>>(&self, name: &str) -> Result<T> {
+        let value = self
+            .values
+            .get(name)
+            .ok_or_else(|| Error::new(format!("unknown parameter '{name}'")))?;
+        value
+            .parse()
+            .map_err(|err| Error::new(format!("invalid value '{value}' for '{name}': {err}")))
+    }
+}
@@ -0,0 +1,186 @@
+use std::{collections::HashMap, fmt::Write, fs, path::Path, time::Duration};
+
+use crate::alloc::{self, AllocStats};
+use crate::config::Config;
+use crate::error::{Error, Result};
+use crate::runner::{DAYS, Day, Solution};
+
+struct DayReport {
+    day: &'static Day,
+    solution: Result<Solution>,
+    expected: HashMap<String, String>,
+    allocs: AllocStats,
+    visual: Option<Result<String>>,
+}
+
+/// Reads `day<N>.answers`, holding lines like `part1: 1234` as printed by
+/// the runner.
+fn read_expected(config: &Config, day: &Day) -> HashMap<String, String> {
+    let Ok(text) = fs::read_to_string(config.answers_path(day.number)) else {
+        return HashMap::new();
+    };
+
+    text.lines()
+        .filter_map(|line| line.split_once(':'))
+        .map(|(label, value)| (label.trim().to_string(), value.trim().to_string()))
+        .collect()
+}
+
+fn run(config: &Config, day: &'static Day) -> DayReport {
+    let path = config.input_path(day.number);
+    let text = fs::read_to_string(&path)
+        .map_err(|err| Error::new(format!("could not read {}: {err}", path.display())));
+    let params = config.params(day);
+
+    let before = alloc::snapshot();
+    let solution = match (&text, &params) {
+        (Ok(text), Ok(params)) => day.run_text(text, params),
+        (Err(err), _) | (_, Err(err)) => Err(err.clone()),
+    };
+    let allocs = alloc::snapshot() - before;
+
+    let visual = match (day.visualize, &text, &params) {
+        (Some(visualize), Ok(text), Ok(params)) => Some(visualize(text, params)),
+        _ => None,
+    };
+
+    DayReport {
+        day,
+        solution,
+        expected: read_expected(config, day),
+        allocs,
+        visual,
+    }
+}
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn phase_colour(label: &str) -> &'static str {
+    match label {
+        "parse" => "#8aa8c8",
+        "part1" => "#5cae6e",
+        "part2" => "#e0904a",
+        _ => "#a67cc8",
+    }
+}
+
+fn total_time(report: &DayReport) -> Duration {
+    match &report.solution {
+        Ok(solution) => solution.timings.iter().map(|(_, elapsed)| *elapsed).sum(),
+        Err(_) => Duration::ZERO,
+    }
+}
+
+fn write_row(html: &mut String, report: &DayReport, longest: Duration) {
+    let number = report.day.number;
+    let solution = match &report.solution {
+        Ok(solution) => solution,
+        Err(err) => {
+            writeln!(
+                html,
+                "<tr><td>day{number}</td><td colspan=\"4\" class=\"wrong\">{}</td></tr>",
+                escape(&err.to_string())
+            )
+            .unwrap();
+            return;
+        }
+    };
+
+    let mut answers = String::new();
+    for (label, value) in solution.answers.iter() {
+        let (class, status) = match report.expected.get(*label) {
+            Some(expected) if expected == value => ("ok", "✓".to_string()),
+            Some(expected) => ("wrong", format!("✗ expected {}", escape(expected))),
+            None => ("unchecked", "?".to_string()),
+        };
+        write!(
+            answers,
+            "<div>{label}: <code>{}</code> <span class=\"{class}\">{status}</span></div>",
+            escape(value)
+        )
+        .unwrap();
+    }
+
+    let mut bar = String::new();
+    let mut legend = String::new();
+    for (label, elapsed) in solution.timings.iter() {
+        let width = if longest.is_zero() {
+            0.0
+        } else {
+            100.0 * elapsed.as_secs_f64() / longest.as_secs_f64()
+        };
+        write!(
+            bar,
+            "<span style=\"width:{width:.3}%;background:{}\" title=\"{label}: {elapsed:.2?}\"></span>",
+            phase_colour(label)
+        )
+        .unwrap();
+        write!(legend, "<div>{label}: {elapsed:.2?}</div>").unwrap();
+    }
+
+    writeln!(
+        html,
+        "<tr><td>day{number}</td><td>{answers}</td><td><div class=\"bar\">{bar}</div></td>\
+         <td>{legend}</td><td>{} allocs<br>{} bytes</td></tr>",
+        report.allocs.allocations, report.allocs.bytes
+    )
+    .unwrap();
+}
+
+fn render(reports: &[DayReport]) -> String {
+    let longest = reports.iter().map(total_time).max().unwrap_or_default();
+
+    let mut html = String::from(
+        "<!DOCTYPE html>\n<html><head><meta charset=\"utf-8\"><title>Advent of Code 2025</title>\n\
+         <style>\n\
+         body { font-family: sans-serif; margin: 2em; }\n\
+         table { border-collapse: collapse; }\n\
+         td, th { border-bottom: 1px solid #ddd; padding: 0.4em 0.8em; vertical-align: top; text-align: left; }\n\
+         .bar { display: flex; width: 20em; height: 1em; background: #eee; }\n\
+         .bar span { display: block; height: 100%; }\n\
+         .ok { color: #2a8a3e; } .wrong { color: #c0392b; } .unchecked { color: #999; }\n\
+         figure { display: inline-block; margin: 1em; } figure svg { width: 24em; height: 24em; }\n\
+         </style></head><body>\n<h1>Advent of Code 2025</h1>\n\
+         <table>\n<tr><th>day</th><th>answers</th><th>time</th><th></th><th>memory</th></tr>\n",
+    );
+
+    for report in reports.iter() {
+        write_row(&mut html, report, longest);
+    }
+    html.push_str("</table>\n");
+
+    for report in reports.iter() {
+        match &report.visual {
+            Some(Ok(svg)) => writeln!(
+                html,
+                "<figure>{svg}<figcaption>day{}</figcaption></figure>",
+                report.day.number
+            )
+            .unwrap(),
+            Some(Err(err)) => writeln!(
+                html,
+                "<p class=\"wrong\">day{} visualization: {}</p>",
+                report.day.number,
+                escape(&err.to_string())
+            )
+            .unwrap(),
+            None => {}
+        }
+    }
+
+    html.push_str("</body></html>\n");
+    html
+}
+
+/// Runs every day and writes the results to a standalone HTML page.
+pub fn write_report(config: &Config, path: &Path) -> Result<()> {
+    let reports: Vec<DayReport> = DAYS.iter().map(|day| run(config, day)).collect();
+
+    fs::write(path, render(&reports))
+        .map_err(|err| Error::new(format!("could not write {}: {err}", path.display())))
+}
@@ -0,0 +1,209 @@
+use std::{
+    fmt::Display,
+    fs,
+    path::Path,
+    time::{Duration, Instant},
+};
+
+use crate::config::{Config, Format};
+use crate::error::{Error, Result};
+use crate::params::{Param, Params};
+use crate::{day1, day2, day3, day4, day5, day6, day7, day8, day9, day10, day11, day12};
+
+/// Answers and timings collected while running a day on one input.
+#[derive(Debug, Default)]
+pub struct Solution {
+    pub answers: Vec<(&'static str, String)>,
+    pub timings: Vec<(&'static str, Duration)>,
+}
+
+impl Solution {
+    pub fn time<T>(&mut self, label: &'static str, f: impl FnOnce() -> T) -> T {
+        let start = Instant::now();
+        let value = f();
+        self.timings.push((label, start.elapsed()));
+        value
+    }
+
+    pub fn answer(&mut self, label: &'static str, value: impl Display) {
+        self.answers.push((label, value.to_string()));
+    }
+
+    /// Times `f` and records its result as the answer for `label`.
+    pub fn part<T: Display>(&mut self, label: &'static str, f: impl FnOnce() -> T) {
+        let value = self.time(label, f);
+        self.answer(label, value);
+    }
+}
+
+/// An extra way of looking at a day's input, run with `cargo run <day> <view>`.
+pub struct View {
+    pub name: &'static str,
+    pub help: &'static str,
+    pub show: fn(&str, &Params) -> Result<()>,
+}
+
+pub struct Day {
+    pub number: u32,
+    pub params: &'static [Param],
+    /// Parses the input and throws it away, used by the fuzzer.
+    pub parse: fn(&str) -> Result<()>,
+    pub run: fn(&str, &Params, &mut Solution) -> Result<()>,
+    /// Renders the input as an SVG image for the HTML report.
+    pub visualize: Option<fn(&str, &Params) -> Result<String>>,
+    pub views: &'static [View],
+}
+
+impl Day {
+    pub fn run_text(&self, text: &str, params: &Params) -> Result<Solution> {
+        let mut solution = Solution::default();
+        (self.run)(text, params, &mut solution)?;
+        Ok(solution)
+    }
+
+    pub fn run_file(&self, path: &Path, params: &Params) -> Result<Solution> {
+        let text = fs::read_to_string(path)
+            .map_err(|err| Error::new(format!("could not read {}: {err}", path.display())))?;
+        self.run_text(&text, params)
+    }
+}
+
+pub const DAYS: [Day; 12] = [
+    day1::DAY,
+    day2::DAY,
+    day3::DAY,
+    day4::DAY,
+    day5::DAY,
+    day6::DAY,
+    day7::DAY,
+    day8::DAY,
+    day9::DAY,
+    day10::DAY,
+    day11::DAY,
+    day12::DAY,
+];
+
+pub fn find_day(name: &str) -> Option<&'static Day> {
+    let number: u32 = name.parse().ok()?;
+    DAYS.iter().find(|day| day.number == number)
+}
+
+const BOLD: &str = "\x1b[1m";
+const GREEN: &str = "\x1b[32m";
+const DIM: &str = "\x1b[2m";
+const RESET: &str = "\x1b[0m";
+
+fn paint(config: &Config, style: &str, text: &str) -> String {
+    if config.color {
+        format!("{style}{text}{RESET}")
+    } else {
+        text.to_string()
+    }
+}
+
+fn json_string(s: &str) -> String {
+    let mut out = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            c if c.is_control() => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+fn print_json(config: &Config, day: &Day, solution: &Solution) {
+    let answers: Vec<String> = solution
+        .answers
+        .iter()
+        .map(|(label, value)| format!("{}:{}", json_string(label), json_string(value)))
+        .collect();
+    let mut fields = vec![
+        format!("\"day\":{}", day.number),
+        format!("\"answers\":{{{}}}", answers.join(",")),
+    ];
+
+    if config.timing {
+        let timings: Vec<String> = solution
+            .timings
+            .iter()
+            .map(|(label, elapsed)| format!("{}:{}", json_string(label), elapsed.as_nanos()))
+            .collect();
+        fields.push(format!("\"timings_ns\":{{{}}}", timings.join(",")));
+    }
+
+    println!("{{{}}}", fields.join(","));
+}
+
+pub fn print_solution(config: &Config, day: &Day, solution: &Solution) {
+    if config.format == Format::Json {
+        print_json(config, day, solution);
+        return;
+    }
+
+    println!(
+        "\n{}",
+        paint(config, BOLD, &format!("--- day{} ---", day.number))
+    );
+    for (label, value) in solution.answers.iter() {
+        println!("{label}: {}", paint(config, GREEN, value));
+    }
+    if config.timing {
+        for (label, elapsed) in solution.timings.iter() {
+            println!(
+                "{}",
+                paint(config, DIM, &format!("{label} took: {elapsed:.2?}"))
+            );
+        }
+    }
+}
+
+pub fn print_params(day: &Day) {
+    if day.params.is_empty() {
+        println!("day{} has no parameters", day.number);
+    }
+    for param in day.params.iter() {
+        println!("{}={}  {}", param.name, param.default, param.help);
+    }
+
+    if !day.views.is_empty() {
+        println!("\nviews:");
+    }
+    for view in day.views.iter() {
+        println!("{}  {}", view.name, view.help);
+    }
+}
+
+pub fn run_view(day: &Day, name: &str, config: &Config) {
+    let Some(view) = day.views.iter().find(|view| view.name == name) else {
+        println!(
+            "day{} has no view '{name}', see `params {}`",
+            day.number, day.number
+        );
+        return;
+    };
+
+    let path = config.input_path(day.number);
+    let result = fs::read_to_string(&path)
+        .map_err(|err| Error::new(format!("could not read {}: {err}", path.display())))
+        .and_then(|text| (view.show)(&text, &config.params(day)?));
+
+    if let Err(err) = result {
+        eprintln!("day{}: {err}", day.number);
+    }
+}
+
+pub fn run_day(day: &Day, config: &Config) {
+    let path = config.input_path(day.number);
+    let result = config
+        .params(day)
+        .and_then(|params| day.run_file(&path, &params));
+
+    match result {
+        Ok(solution) => print_solution(config, day, &solution),
+        Err(err) => eprintln!("day{}: {err}", day.number),
+    }
+}
@@ -0,0 +1,26 @@
+use std::fmt::Write;
+
+/// Draws unit cells on a `width` x `height` grid, one colour per layer.
+pub fn grid(width: i64, height: i64, layers: &[(&str, Vec<(i64, i64)>)]) -> String {
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"0 0 {width} {height}\" \
+         shape-rendering=\"crispEdges\">"
+    );
+    write!(
+        svg,
+        "<rect width=\"{width}\" height=\"{height}\" fill=\"#f4f4f4\"/>"
+    )
+    .unwrap();
+
+    for (colour, cells) in layers.iter() {
+        // a single path per layer keeps large grids small
+        let mut path = String::new();
+        for (x, y) in cells.iter() {
+            write!(path, "M{x} {y}h1v1h-1z").unwrap();
+        }
+        write!(svg, "<path fill=\"{colour}\" d=\"{path}\"/>").unwrap();
+    }
+
+    svg.push_str("</svg>");
+    svg
+}
@@ -0,0 +1,116 @@
+use std::{
+    collections::HashMap,
+    fs,
+    panic::{self, AssertUnwindSafe},
+    path::{Path, PathBuf},
+    thread,
+    time::{Duration, SystemTime},
+};
+
+use crate::config::Config;
+use crate::runner::{Day, Solution};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+struct Watched {
+    modified: SystemTime,
+    answers: Vec<(&'static str, String)>,
+}
+
+/// The day's input followed by any `day<N>_example*.txt` files next to it.
+fn watched_files(day: &Day, config: &Config) -> Vec<PathBuf> {
+    let prefix = format!("day{}_example", day.number);
+    let mut examples: Vec<PathBuf> = fs::read_dir(&config.input_dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with(&prefix) && name.ends_with(".txt"))
+        })
+        .collect();
+    examples.sort();
+
+    let mut files = vec![config.input_path(day.number)];
+    files.extend(examples);
+    files
+}
+
+fn print_diff(path: &Path, solution: &Solution, previous: Option<&Watched>) {
+    println!("\n--- {} ---", path.display());
+
+    for (label, value) in solution.answers.iter() {
+        let before = previous.and_then(|watched| {
+            watched
+                .answers
+                .iter()
+                .find(|(l, _)| l == label)
+                .map(|(_, value)| value)
+        });
+
+        match before {
+            None => println!("{label}: {value}"),
+            Some(before) if before == value => println!("{label}: {value} (unchanged)"),
+            Some(before) => println!("{label}: {value} (was {before})"),
+        }
+    }
+    for (label, elapsed) in solution.timings.iter() {
+        println!("{label} took: {elapsed:.2?}");
+    }
+}
+
+/// Polls the day's input and example files, re-running the day whenever one
+/// of them is modified. Only returns if the day's parameters are invalid.
+pub fn watch(day: &Day, config: &Config) {
+    let params = match config.params(day) {
+        Ok(params) => params,
+        Err(err) => {
+            eprintln!("day{}: {err}", day.number);
+            return;
+        }
+    };
+
+    println!("watching day{} inputs, press ctrl-c to stop", day.number);
+    let mut seen: HashMap<PathBuf, Watched> = HashMap::new();
+
+    loop {
+        for path in watched_files(day, config) {
+            let Ok(modified) = fs::metadata(&path).and_then(|meta| meta.modified()) else {
+                continue;
+            };
+            if seen.get(&path).is_some_and(|w| w.modified == modified) {
+                continue;
+            }
+
+            let Ok(text) = fs::read_to_string(&path) else {
+                continue;
+            };
+
+            // A half-edited input can easily trip a parser, so keep watching
+            // instead of taking the whole loop down with it.
+            let result = panic::catch_unwind(AssertUnwindSafe(|| day.run_text(&text, &params)));
+            let Ok(Ok(solution)) = result else {
+                if let Ok(Err(err)) = result {
+                    println!("{}: {err}", path.display());
+                }
+                println!("{} failed, waiting for the next change", path.display());
+                let answers = seen.remove(&path).map(|w| w.answers).unwrap_or_default();
+                seen.insert(path, Watched { modified, answers });
+                continue;
+            };
+
+            print_diff(&path, &solution, seen.get(&path));
+            seen.insert(
+                path,
+                Watched {
+                    modified,
+                    answers: solution.answers,
+                },
+            );
+        }
+
+        thread::sleep(POLL_INTERVAL);
+    }
+}